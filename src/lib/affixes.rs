@@ -0,0 +1,277 @@
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::*;
+
+// 装备的随机词缀
+// 武器和护甲生成时 有一定的几率带上一个前缀 和 一个后缀，比如 "Flaming Longsword of Protection"
+// 越深的地方 出现词缀的几率越大，也会出现更强的词缀
+
+#[derive(PartialEq, Copy, Clone)]
+enum AffixKind {
+    Prefix,
+    Suffix,
+}
+
+// 词缀可以用在哪一类装备上
+#[derive(PartialEq, Copy, Clone)]
+enum AffixTarget {
+    Weapon,
+    Armor,
+    Any,
+}
+
+#[derive(Copy, Clone)]
+enum AffixEffect {
+    Power(i32),
+    Defense(i32),
+    Strength(i32),
+    Elemental(DamageType, i32),
+    LifeSteal(i32),
+    Confuse(i32),
+}
+
+struct Affix {
+    name: &'static str,
+    kind: AffixKind,
+    target: AffixTarget,
+    // 从第几层开始出现
+    min_depth: i32,
+    weight: i32,
+    effect: AffixEffect,
+    // 增加的价格
+    value: i32,
+}
+
+const AFFIXES: &[Affix] = &[
+    Affix {
+        name: "Keen",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Weapon,
+        min_depth: 1,
+        weight: 10,
+        effect: AffixEffect::Power(1),
+        value: 20,
+    },
+    Affix {
+        name: "Sturdy",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Armor,
+        min_depth: 1,
+        weight: 10,
+        effect: AffixEffect::Defense(1),
+        value: 20,
+    },
+    Affix {
+        name: "Mighty",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Any,
+        min_depth: 2,
+        weight: 5,
+        effect: AffixEffect::Strength(2),
+        value: 30,
+    },
+    Affix {
+        name: "Flaming",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Weapon,
+        min_depth: 2,
+        weight: 5,
+        effect: AffixEffect::Elemental(DamageType::Fire, 2),
+        value: 50,
+    },
+    Affix {
+        name: "Frozen",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Weapon,
+        min_depth: 3,
+        weight: 4,
+        effect: AffixEffect::Elemental(DamageType::Frost, 3),
+        value: 60,
+    },
+    Affix {
+        name: "Venomous",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Weapon,
+        min_depth: 3,
+        weight: 4,
+        effect: AffixEffect::Elemental(DamageType::Poison, 2),
+        value: 50,
+    },
+    Affix {
+        name: "Vampiric",
+        kind: AffixKind::Prefix,
+        target: AffixTarget::Weapon,
+        min_depth: 4,
+        weight: 2,
+        effect: AffixEffect::LifeSteal(2),
+        value: 100,
+    },
+    Affix {
+        name: "of Protection",
+        kind: AffixKind::Suffix,
+        target: AffixTarget::Any,
+        min_depth: 1,
+        weight: 10,
+        effect: AffixEffect::Defense(1),
+        value: 25,
+    },
+    Affix {
+        name: "of Strength",
+        kind: AffixKind::Suffix,
+        target: AffixTarget::Any,
+        min_depth: 1,
+        weight: 8,
+        effect: AffixEffect::Strength(3),
+        value: 30,
+    },
+    Affix {
+        name: "of Power",
+        kind: AffixKind::Suffix,
+        target: AffixTarget::Any,
+        min_depth: 2,
+        weight: 6,
+        effect: AffixEffect::Power(1),
+        value: 40,
+    },
+    Affix {
+        name: "of Confusion",
+        kind: AffixKind::Suffix,
+        target: AffixTarget::Weapon,
+        min_depth: 3,
+        weight: 3,
+        effect: AffixEffect::Confuse(2),
+        value: 60,
+    },
+];
+
+// 当前深度 可以用在这件装备上的词缀表
+fn affix_table(kind: AffixKind, is_weapon: bool, depth: i32) -> RandomTable {
+    let mut table = RandomTable::new();
+    for affix in AFFIXES.iter() {
+        let fits = match affix.target {
+            AffixTarget::Weapon => is_weapon,
+            AffixTarget::Armor => !is_weapon,
+            AffixTarget::Any => true,
+        };
+        if affix.kind == kind && fits && affix.min_depth <= depth {
+            table = table.add(affix.name, affix.weight);
+        }
+    }
+    table
+}
+
+// 为刚生成的装备 随机加上词缀，已经带有词缀的装备 不再添加
+pub fn roll_affixes(ecs: &mut World, item: Entity, depth: i32) {
+    if ecs.read_storage::<Affixed>().get(item).is_some() {
+        return;
+    }
+    let is_weapon = match ecs.read_storage::<Equippable>().get(item) {
+        Some(equippable) => equippable.slot == EquipmentSlot::Melee,
+        None => return,
+    };
+
+    // 每个词缀 出现的几率 随着深度增加
+    let chance = i32::min(50, 5 + depth * 5);
+    let mut chosen: Vec<&Affix> = Vec::new();
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        for kind in [AffixKind::Prefix, AffixKind::Suffix].iter() {
+            if rng.roll_dice(1, 100) > chance {
+                continue;
+            }
+            let name = affix_table(*kind, is_weapon, depth).roll(&mut rng);
+            if let Some(affix) = AFFIXES.iter().find(|a| a.name == name) {
+                chosen.push(affix);
+            }
+        }
+    }
+    if chosen.is_empty() {
+        return;
+    }
+
+    for affix in chosen.iter() {
+        apply_affix(ecs, item, affix);
+    }
+
+    // 带词缀的装备 直接显示完整的名字
+    let mut names = ecs.write_storage::<Name>();
+    if let Some(name) = names.get_mut(item) {
+        let base_name = name.name.clone();
+        let mut full_name = base_name.clone();
+        for affix in chosen.iter() {
+            full_name = match affix.kind {
+                AffixKind::Prefix => format!("{} {}", affix.name, full_name),
+                AffixKind::Suffix => format!("{} {}", full_name, affix.name),
+            };
+        }
+        name.name = full_name;
+        ecs.write_storage::<Affixed>()
+            .insert(item, Affixed { base_name })
+            .expect("Unable to insert affixed");
+    }
+    ecs.write_storage::<ObfuscatedName>().remove(item);
+}
+
+fn apply_affix(ecs: &mut World, item: Entity, affix: &Affix) {
+    match affix.effect {
+        AffixEffect::Power(power) => {
+            let mut bonuses = ecs.write_storage::<MeleePowerBonus>();
+            match bonuses.get_mut(item) {
+                Some(bonus) => bonus.power += power,
+                None => {
+                    bonuses
+                        .insert(item, MeleePowerBonus { power })
+                        .expect("Unable to insert power bonus");
+                }
+            }
+        }
+        AffixEffect::Defense(defense) => {
+            let mut bonuses = ecs.write_storage::<DefenseBonus>();
+            match bonuses.get_mut(item) {
+                Some(bonus) => bonus.defense += defense,
+                None => {
+                    bonuses
+                        .insert(item, DefenseBonus { defense })
+                        .expect("Unable to insert defense bonus");
+                }
+            }
+        }
+        AffixEffect::Strength(strength) => {
+            let mut bonuses = ecs.write_storage::<AttributeBonus>();
+            match bonuses.get_mut(item) {
+                Some(bonus) => bonus.strength += strength,
+                None => {
+                    bonuses
+                        .insert(item, AttributeBonus { strength })
+                        .expect("Unable to insert attribute bonus");
+                }
+            }
+        }
+        AffixEffect::Elemental(damage_type, damage) => {
+            ecs.write_storage::<ElementalDamage>()
+                .insert(
+                    item,
+                    ElementalDamage {
+                        damage_type,
+                        damage,
+                    },
+                )
+                .expect("Unable to insert elemental damage");
+        }
+        AffixEffect::LifeSteal(amount) => {
+            ecs.write_storage::<LifeSteal>()
+                .insert(item, LifeSteal { amount })
+                .expect("Unable to insert life steal");
+        }
+        AffixEffect::Confuse(turns) => {
+            ecs.write_storage::<ConfuseOnHit>()
+                .insert(item, ConfuseOnHit { turns })
+                .expect("Unable to insert confuse on hit");
+        }
+    }
+
+    if let Some(value) = ecs.write_storage::<ItemValue>().get_mut(item) {
+        value.value += affix.value;
+    }
+}
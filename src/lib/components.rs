@@ -45,6 +45,12 @@ pub struct Name {
     pub name: String,
 }
 
+// 较长的描述，在观察模式中显示
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct Description {
+    pub text: String,
+}
+
 // 玩家 和 怪物都应该有这个组件，被阻挡的TIle
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
@@ -65,26 +71,6 @@ pub struct WantsToMelee {
     pub target: Entity,
 }
 
-// 遭受的攻击
-#[derive(Component, ConvertSaveload, Clone, Debug)]
-pub struct SufferDamage {
-    // 遭受多个攻击
-    pub amount: Vec<i32>,
-}
-impl SufferDamage {
-    // 新的攻击，伤害值
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
-        if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
-        } else {
-            let dmg = SufferDamage {
-                amount: vec![amount],
-            };
-            store.insert(victim, dmg).expect("Unable to insert damage");
-        }
-    }
-}
-
 // item 定义物品的组件
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Item {}
@@ -161,14 +147,28 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    // 已知物品的登记表
+    pub item_registry: super::identification::ItemRegistry,
+    // 游戏日志
+    pub game_log: super::gamelog::GameLog,
 }
 
 // -------------------------------- 让物品可以被装备 --------------------------------
 // 装备插槽
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+// Melee 是主手，Shield 是副手
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Head,
+    Torso,
+    Legs,
+    Feet,
+    Hands,
+    Ring,
+    Amulet,
+    // 弓 和 弩
+    Ranged,
 }
 
 // 可装备的
@@ -177,6 +177,10 @@ pub struct Equippable {
     pub slot: EquipmentSlot,
 }
 
+// 双手武器，装备在 Melee 槽，同时占用 Shield 槽
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct TwoHanded {}
+
 // 表示被装备
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Equipped {
@@ -241,3 +245,273 @@ pub struct EntityMoved {}
 // 只会触发一次的陷阱
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct SingleActivation {}
+
+// 把目标传送到地图上随机的位置
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RandomTeleport {}
+
+// 警报，叫醒 radius 范围内的怪物
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Alarm {
+    pub radius: i32,
+}
+
+// 让目标中毒
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct InflictsPoison {
+    pub turns: i32,
+    pub damage: i32,
+}
+
+// 中毒状态，每回合受到伤害
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Poisoned {
+    pub turns: i32,
+    pub damage: i32,
+}
+
+// 陷坑，掉到下一层
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TrapDoor {}
+
+// 在目标周围召唤怪物
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct SummonsMonsters {
+    pub count: i32,
+}
+
+// 睡着的怪物，被吵醒 或者 受到攻击前不会行动
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Asleep {}
+
+// 物品鉴定
+// 未鉴定的物品显示的名字
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct ObfuscatedName {
+    pub name: String,
+}
+
+// 鉴定卷轴
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesIdentification {}
+
+// 被诅咒的装备，装备后无法卸下
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Cursed {}
+
+// 解除诅咒卷轴
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesRemoveCurse {}
+
+// 可以多次使用的物品，比如魔杖
+// 同时是 Consumable 的物品 充能用完后会碎掉，否则保留下来等待充能
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Charges {
+    pub current: i32,
+    pub max: i32,
+}
+
+// 充能卷轴
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesRecharge {}
+
+// 可以堆叠的物品，相同名字的物品 在背包中合并成一堆
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Quantity {
+    pub amount: i32,
+}
+
+// 物品的重量，单位是磅
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Weight {
+    pub weight: f32,
+}
+
+// 角色的属性，力量决定了负重上限，感知决定了发现 和 解除陷阱的几率
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Attributes {
+    pub strength: i32,
+    pub perception: i32,
+}
+
+// 当前的负重 和 负重上限，由 EncumbranceSystem 每回合更新
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Burden {
+    pub carried: f32,
+    pub capacity: f32,
+}
+
+impl Burden {
+    pub fn is_encumbered(&self) -> bool {
+        self.carried > self.capacity
+    }
+}
+
+// 杀死的怪物数量，显示在角色面板上
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct KillCount {
+    pub kills: i32,
+}
+
+// -------------------------------- 附魔的装备 --------------------------------
+// 伤害的类型
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum DamageType {
+    Fire,
+    Frost,
+    Poison,
+}
+
+// 武器附带的 元素伤害，不受防御的影响
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct ElementalDamage {
+    pub damage_type: DamageType,
+    pub damage: i32,
+}
+
+// 装备增加的属性
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct AttributeBonus {
+    pub strength: i32,
+}
+
+// 命中时 为攻击者恢复生命
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct LifeSteal {
+    pub amount: i32,
+}
+
+// 命中时 有几率让目标混乱
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct ConfuseOnHit {
+    pub turns: i32,
+}
+
+// 已经带有随机词缀的装备，记住原来的名字，合成时 按原来的名字匹配材料
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Affixed {
+    pub base_name: String,
+}
+
+// -------------------------------- 远程武器 --------------------------------
+// 弹药的种类，远程武器 只能使用对应的弹药
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum AmmoType {
+    Arrow,
+    Bolt,
+}
+
+// 远程武器，装备在 Ranged 槽中
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub power: i32,
+    pub ammo: AmmoType,
+}
+
+// 弹药，成堆的放在背包中，每次射击消耗一个
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Ammunition {
+    pub ammo: AmmoType,
+}
+
+// 射击的意图，target 是投射物的落点
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot {
+    pub target: rltk::Point,
+}
+
+// -------------------------------- 投掷 --------------------------------
+// 药水，扔出去时 摔碎并作用于落点周围
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Potion {}
+
+// 投掷物品的意图，target 是物品的落点
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToThrow {
+    pub item: Entity,
+    pub target: rltk::Point,
+}
+
+// -------------------------------- 机关 --------------------------------
+// 机关触发后 对目标 tile 做的事情
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum MechanismAction {
+    // 打开 或者 关上门
+    ToggleDoors,
+    // 打开关着怪物的闸门，并叫醒它们
+    ReleaseMonsters,
+    // 墙倒塌 变成地面
+    CollapseWalls,
+    // 地面被水淹没
+    Flood,
+}
+
+// 远程机关，由 拉杆 或者 压力板 触发，作用于 targets 上的 tile
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Mechanism {
+    pub action: MechanismAction,
+    pub targets: Vec<rltk::Point>,
+}
+
+// 拉杆，撞上去就会拉动
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Lever {
+    pub pulled: bool,
+}
+
+// -------------------------------- 法术 --------------------------------
+// 法力值，施法时消耗
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct Mana {
+    pub current: i32,
+    pub max: i32,
+}
+
+// 法术，和物品一样 由身上的组件决定效果
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct Spell {
+    pub mana_cost: i32,
+}
+
+// 学会的法术，owner 是学会这个法术的实体
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct KnownSpell {
+    pub owner: Entity,
+}
+
+// 法术书，阅读后学会其中的法术
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct TeachesSpell {
+    pub spell: String,
+}
+
+// 施法的意图
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToCastSpell {
+    pub spell: Entity,
+    pub target: Option<rltk::Point>,
+}
+
+// -------------------------------- 金币 和 商店 --------------------------------
+// 身上带着的金币，怪物死后会掉落
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Wallet {
+    pub gold: i32,
+}
+
+// 地上的一堆金币，捡起来后放进钱包
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct Gold {
+    pub amount: i32,
+}
+
+// 物品的基础价格，卖出的价格是它的一半
+#[derive(Component, ConvertSaveload, Clone, Debug)]
+pub struct ItemValue {
+    pub value: i32,
+}
+
+// 商人，货物放在自己的背包中
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vendor {}
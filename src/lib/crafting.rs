@@ -0,0 +1,134 @@
+use specs::prelude::*;
+
+use crate::*;
+
+// 物品合成
+// 配方用数据描述：需要哪些材料 各需要几个，合成出什么物品
+// 合成的物品和地上捡到的物品一样，通过 spawner 生成
+
+pub struct Recipe {
+    pub result: &'static str,
+    pub ingredients: &'static [(&'static str, i32)],
+}
+
+pub const RECIPES: &[Recipe] = &[
+    Recipe {
+        result: "Travel Meal",
+        ingredients: &[("Rations", 2)],
+    },
+    Recipe {
+        result: "Sharp Dagger",
+        ingredients: &[("Dagger", 1), ("Whetstone", 1)],
+    },
+];
+
+impl Recipe {
+    // 配方的描述，比如 "Sharp Dagger: Dagger, Whetstone"
+    pub fn describe(&self) -> String {
+        let ingredients: Vec<String> = self
+            .ingredients
+            .iter()
+            .map(|(name, count)| {
+                if *count > 1 {
+                    format!("{} x{}", name, count)
+                } else {
+                    name.to_string()
+                }
+            })
+            .collect();
+        format!("{}: {}", self.result, ingredients.join(", "))
+    }
+}
+
+// 物品原来的名字，带词缀的装备 按原来的名字作为材料
+fn base_name<'a>(name: &'a Name, affixed: Option<&'a Affixed>) -> &'a str {
+    affixed.map_or(name.name.as_str(), |a| a.base_name.as_str())
+}
+
+// owner 的背包中 某种物品的数量
+fn count_in_backpack(ecs: &World, owner: Entity, name: &str) -> i32 {
+    let names = ecs.read_storage::<Name>();
+    let affixed = ecs.read_storage::<Affixed>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let quantities = ecs.read_storage::<Quantity>();
+    let entities = ecs.entities();
+    (&entities, &names, &backpack)
+        .join()
+        .filter(|(entity, n, pack)| {
+            pack.owner == owner && base_name(n, affixed.get(*entity)) == name
+        })
+        .map(|(entity, _, _)| quantities.get(entity).map_or(1, |q| q.amount))
+        .sum()
+}
+
+pub fn can_craft(ecs: &World, owner: Entity, recipe: &Recipe) -> bool {
+    recipe
+        .ingredients
+        .iter()
+        .all(|(name, count)| count_in_backpack(ecs, owner, name) >= *count)
+}
+
+// 从背包中 用掉 count 个某种物品
+fn consume_ingredient(ecs: &mut World, owner: Entity, name: &str, count: i32) {
+    let mut remaining = count;
+    let mut to_delete: Vec<Entity> = Vec::new();
+    {
+        let names = ecs.read_storage::<Name>();
+        let affixed = ecs.read_storage::<Affixed>();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let mut quantities = ecs.write_storage::<Quantity>();
+        let entities = ecs.entities();
+        let mut candidates: Vec<Entity> = (&entities, &names, &backpack)
+            .join()
+            .filter(|(entity, n, pack)| {
+                pack.owner == owner && base_name(n, affixed.get(*entity)) == name
+            })
+            .map(|(entity, _, _)| entity)
+            .collect();
+        // 先用掉没有词缀的普通物品
+        candidates.sort_by_key(|entity| affixed.get(*entity).is_some());
+        for entity in candidates {
+            if remaining < 1 {
+                break;
+            }
+            match quantities.get_mut(entity) {
+                Some(quantity) if quantity.amount > remaining => {
+                    quantity.amount -= remaining;
+                    remaining = 0;
+                }
+                Some(quantity) => {
+                    remaining -= quantity.amount;
+                    to_delete.push(entity);
+                }
+                None => {
+                    remaining -= 1;
+                    to_delete.push(entity);
+                }
+            }
+        }
+    }
+    for entity in to_delete {
+        ecs.delete_entity(entity)
+            .expect("Unable to delete ingredient");
+    }
+}
+
+// 合成，成功返回 true
+pub fn craft(ecs: &mut World, recipe: &Recipe) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    if !can_craft(ecs, player_entity, recipe) {
+        ecs.fetch_mut::<GameLog>().push(format!(
+            "You don't have the ingredients for {}.",
+            recipe.result
+        ));
+        return false;
+    }
+
+    for (name, count) in recipe.ingredients.iter() {
+        consume_ingredient(ecs, player_entity, name, *count);
+    }
+    spawner::spawn_in_backpack(ecs, recipe.result, player_entity);
+    ecs.fetch_mut::<GameLog>()
+        .push(format!("You craft {}.", recipe.result));
+    true
+}
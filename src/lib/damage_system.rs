@@ -1,60 +1,47 @@
-use crate::{GameLog, Map, Name, Player, Position, RunState};
+use crate::{spawner, GameLog, Name, Player, Position, RunState, Wallet};
 
-use super::{CombatStats, SufferDamage};
+use super::CombatStats;
 use specs::prelude::*;
 
-// 该系统来计算伤害值
-pub struct DamageSystem {}
-
-impl<'a> System<'a> for DamageSystem {
-    type SystemData = (
-        WriteStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
-        ReadStorage<'a, Position>,
-        WriteExpect<'a, Map>,
-        Entities<'a>,
-    );
-
-    fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            // 使用迭代器 dui damage 进行求和
-            stats.hp -= damage.amount.iter().sum::<i32>();
-            // 在受攻击实体的位置渲染血迹
-            let pos = positions.get(entity);
-            if let Some(pos) = pos {
-                let idx = map.xy_idx(pos.x, pos.y);
-                map.bloodstains.insert(idx);
-            }
-        }
-        damage.clear();
-    }
-}
+// 伤害的计算 在 effects::damage 中
 // add a method to clean up dead entities 删除 实体
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    // 死去的怪物掉落的金币，位置 和 数量
+    let mut dropped_gold: Vec<(i32, i32, i32)> = Vec::new();
     // 使用作用域来让 借用检查 高兴
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let players = ecs.read_storage::<Player>();
         let names = ecs.read_storage::<Name>();
+        let positions = ecs.read_storage::<Position>();
+        let wallets = ecs.read_storage::<Wallet>();
         let entities = ecs.entities();
         let mut log = ecs.write_resource::<GameLog>();
         for (entity, stats) in (&entities, &combat_stats).join() {
-            // 得到玩家
-            let player = players.get(entity);
-            match player {
-                None => {
-                    let victim_name = names.get(entity);
-                    if let Some(victim_name) = victim_name {
-                        log.entries.push(format!("{} is dead", &victim_name.name));
+            if stats.hp < 1 {
+                // 得到玩家
+                let player = players.get(entity);
+                match player {
+                    None => {
+                        let victim_name = names.get(entity);
+                        if let Some(victim_name) = victim_name {
+                            log.push(format!("{} is dead", &victim_name.name));
+                        }
+                        if let (Some(pos), Some(wallet)) =
+                            (positions.get(entity), wallets.get(entity))
+                        {
+                            if wallet.gold > 0 {
+                                dropped_gold.push((pos.x, pos.y, wallet.gold));
+                            }
+                        }
+                        dead.push(entity)
+                    }
+                    // 游戏结束
+                    Some(_) => {
+                        let mut runstate = ecs.write_resource::<RunState>();
+                        *runstate = RunState::GameOver;
                     }
-                    dead.push(entity)
-                }
-                // 游戏结束
-                Some(_) => {
-                    let mut runstate = ecs.write_resource::<RunState>();
-                    *runstate = RunState::GameOver;
                 }
             }
         }
@@ -62,4 +49,7 @@ pub fn delete_the_dead(ecs: &mut World) {
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
+    for (x, y, amount) in dropped_gold {
+        spawner::gold(ecs, x, y, amount);
+    }
 }
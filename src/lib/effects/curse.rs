@@ -0,0 +1,57 @@
+use crate::{
+    Cursed, DefenseBonus, Equipped, GameLog, InBackpack, MeleePowerBonus, Name, ObfuscatedName,
+};
+use specs::prelude::*;
+
+// 解除目标 背包中 和 身上装备的 所有诅咒，诅咒带来的负面加成也一起消失
+pub fn remove_curse(ecs: &mut World, target: Entity) {
+    let mut lifted: Vec<Entity> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let cursed = ecs.read_storage::<Cursed>();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let equipped = ecs.read_storage::<Equipped>();
+        for (entity, _cursed) in (&entities, &cursed).join() {
+            let owned = backpack.get(entity).is_some_and(|b| b.owner == target)
+                || equipped.get(entity).is_some_and(|e| e.owner == target);
+            if owned {
+                lifted.push(entity);
+            }
+        }
+    }
+
+    for item in lifted.iter() {
+        ecs.write_storage::<Cursed>().remove(*item);
+        {
+            let mut power = ecs.write_storage::<MeleePowerBonus>();
+            if power.get(*item).is_some_and(|p| p.power < 0) {
+                power.remove(*item);
+            }
+        }
+        {
+            let mut defense = ecs.write_storage::<DefenseBonus>();
+            if defense.get(*item).is_some_and(|d| d.defense < 0) {
+                defense.remove(*item);
+            }
+        }
+        // 诅咒解除后，就是一件普通的装备
+        let base_name = ecs
+            .read_storage::<ObfuscatedName>()
+            .get(*item)
+            .map(|o| o.name.clone());
+        if let Some(base_name) = base_name {
+            if let Some(name) = ecs.write_storage::<Name>().get_mut(*item) {
+                name.name = base_name;
+            }
+        }
+    }
+
+    if target == *ecs.fetch::<Entity>() {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        if lifted.is_empty() {
+            gamelog.push("You feel a brief warmth, but nothing happens.");
+        } else {
+            gamelog.push("You feel as if someone is watching over you.");
+        }
+    }
+}
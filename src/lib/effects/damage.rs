@@ -0,0 +1,121 @@
+use crate::{
+    Asleep, CombatStats, Confusion, GameLog, HungerClock, HungerState, KillCount, Map, Poisoned,
+    Position,
+};
+use specs::prelude::*;
+
+use super::{EffectQueue, EffectSpawner, EffectType, Targets};
+
+// 造成伤害，在受攻击实体的位置渲染血迹
+pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
+    if let EffectType::Damage { amount } = damage.effect_type {
+        let mut alive = false;
+        let mut killed = false;
+        if let Some(stats) = ecs.write_storage::<CombatStats>().get_mut(target) {
+            if stats.hp > 0 {
+                stats.hp -= amount;
+                alive = true;
+                killed = stats.hp <= 0;
+            }
+        }
+        if !alive {
+            return;
+        }
+        // 记在造成致命伤害的实体头上
+        if let (true, Some(creator)) = (killed, damage.creator) {
+            if let Some(count) = ecs.write_storage::<KillCount>().get_mut(creator) {
+                count.kills += 1;
+            }
+        }
+        // 受到攻击 会把睡着的怪物弄醒
+        ecs.write_storage::<Asleep>().remove(target);
+        if let Some(pos) = ecs.read_storage::<Position>().get(target) {
+            let mut map = ecs.fetch_mut::<Map>();
+            let idx = map.xy_idx(pos.x, pos.y);
+            map.bloodstains.insert(idx);
+        }
+        ecs.write_resource::<EffectQueue>().add_effect(
+            None,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('‼'),
+                fg: rltk::RGB::named(rltk::ORANGE),
+                bg: rltk::RGB::named(rltk::BLACK),
+                lifetime: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
+
+// 恢复生命值，不超过最大生命值
+pub fn heal_damage(ecs: &mut World, heal: &EffectSpawner, target: Entity) {
+    if let EffectType::Healing { amount } = heal.effect_type {
+        if let Some(stats) = ecs.write_storage::<CombatStats>().get_mut(target) {
+            stats.hp = i32::min(stats.max_hp, stats.hp + amount);
+        } else {
+            return;
+        }
+        ecs.write_resource::<EffectQueue>().add_effect(
+            None,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('♥'),
+                fg: rltk::RGB::named(rltk::GREEN),
+                bg: rltk::RGB::named(rltk::BLACK),
+                lifetime: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
+
+// 昏迷状态
+pub fn add_confusion(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Confusion { turns } = effect.effect_type {
+        ecs.write_storage::<Confusion>()
+            .insert(target, Confusion { turns })
+            .expect("Unable to insert status");
+        ecs.write_resource::<EffectQueue>().add_effect(
+            None,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('?'),
+                fg: rltk::RGB::named(rltk::MAGENTA),
+                bg: rltk::RGB::named(rltk::BLACK),
+                lifetime: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
+
+// 中毒状态，由 PoisonSystem 每回合造成伤害
+pub fn add_poison(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Poison { turns, damage } = effect.effect_type {
+        if ecs.read_storage::<CombatStats>().get(target).is_none() {
+            return;
+        }
+        ecs.write_storage::<Poisoned>()
+            .insert(target, Poisoned { turns, damage })
+            .expect("Unable to insert status");
+        if target == *ecs.fetch::<Entity>() {
+            ecs.fetch_mut::<GameLog>().push("You are poisoned!");
+        }
+        ecs.write_resource::<EffectQueue>().add_effect(
+            None,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('♣'),
+                fg: rltk::RGB::named(rltk::GREEN),
+                bg: rltk::RGB::named(rltk::BLACK),
+                lifetime: 200.0,
+            },
+            Targets::Single { target },
+        );
+    }
+}
+
+// 吃饱了
+pub fn well_fed(ecs: &mut World, target: Entity) {
+    if let Some(hc) = ecs.write_storage::<HungerClock>().get_mut(target) {
+        hc.state = HungerState::WellFed;
+        hc.duration = 20;
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{get_item_display_name, GameLog, ItemRegistry, Name, RunState};
+use specs::prelude::*;
+
+use super::{consume_item, EffectSpawner, EffectType};
+
+// 只有玩家可以使用鉴定菜单
+pub fn show_identify_menu(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if target != *ecs.fetch::<Entity>() {
+        return;
+    }
+    if let EffectType::Identify { scroll } = effect.effect_type {
+        let mut runstate = ecs.write_resource::<RunState>();
+        *runstate = RunState::ShowIdentify { scroll };
+    }
+}
+
+// 鉴定选中的物品，鉴定完成后 才消耗鉴定卷轴
+pub fn identify_item(ecs: &mut World, scroll: Entity, item: Entity) {
+    let old_name = get_item_display_name(ecs, item);
+    let name = match ecs.read_storage::<Name>().get(item) {
+        Some(name) => name.name.clone(),
+        None => return,
+    };
+    ecs.write_resource::<ItemRegistry>().identify(&name);
+    ecs.fetch_mut::<GameLog>()
+        .push(format!("You identify {} as {}.", old_name, name));
+    consume_item(ecs, scroll);
+}
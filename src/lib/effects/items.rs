@@ -0,0 +1,15 @@
+use crate::{split_stack, InBackpack, Position};
+use specs::prelude::*;
+
+use super::{EffectSpawner, EffectType};
+
+// 物品落到地上，成堆的物品 只落下一个
+pub fn land_item(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::LandItem { x, y } = effect.effect_type {
+        let item = split_stack(ecs, target);
+        ecs.write_storage::<InBackpack>().remove(item);
+        ecs.write_storage::<Position>()
+            .insert(item, Position { x, y })
+            .expect("Unable to insert position");
+    }
+}
@@ -0,0 +1,95 @@
+use rltk::Point;
+use specs::prelude::*;
+
+use super::{EffectQueue, EffectSpawner, EffectType, Targets};
+use crate::{tile_blocks, Asleep, CombatStats, Map, MechanismAction, Position, TileType, Viewshed};
+
+// 被释放的怪物 会被叫醒的范围
+const RELEASE_RADIUS: f32 = 8.0;
+// 被水淹没时 受到的伤害
+const FLOOD_DAMAGE: i32 = 3;
+
+// 机关作用于一个 tile
+pub fn activate(ecs: &mut World, effect: &EffectSpawner, tile_idx: i32) {
+    if let EffectType::Mechanism { action, tile } = effect.effect_type {
+        let idx = tile_idx as usize;
+        let changed = {
+            let mut map = ecs.fetch_mut::<Map>();
+            let old_tile = map.tiles[idx];
+            let new_tile = match (action, old_tile) {
+                (MechanismAction::ToggleDoors, TileType::ClosedDoor) => TileType::OpenDoor,
+                (MechanismAction::ToggleDoors, TileType::OpenDoor) => TileType::ClosedDoor,
+                (MechanismAction::ReleaseMonsters, TileType::ClosedDoor) => TileType::Floor,
+                (MechanismAction::CollapseWalls, TileType::Wall) => TileType::Floor,
+                (MechanismAction::Flood, TileType::Floor) => TileType::Water,
+                (_, tile) => tile,
+            };
+            map.tiles[idx] = new_tile;
+            // 下一次建立索引之前 就要挡住 或者放行
+            if tile_blocks(new_tile) {
+                map.blocked[idx] = true;
+            } else if tile_blocks(old_tile) {
+                map.blocked[idx] = false;
+            }
+            old_tile != new_tile
+        };
+        if !changed {
+            return;
+        }
+
+        match action {
+            MechanismAction::ReleaseMonsters => wake_monsters_near(ecs, tile),
+            MechanismAction::Flood => {
+                let content = ecs.fetch::<Map>().tile_content[idx].clone();
+                let combat_stats = ecs.read_storage::<CombatStats>();
+                let mut effects = ecs.write_resource::<EffectQueue>();
+                for entity in content.iter().filter(|e| combat_stats.get(**e).is_some()) {
+                    effects.add_effect(
+                        effect.creator,
+                        EffectType::Damage {
+                            amount: FLOOD_DAMAGE,
+                        },
+                        Targets::Single { target: *entity },
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        ecs.write_resource::<EffectQueue>().add_effect(
+            None,
+            EffectType::Particle {
+                glyph: rltk::to_cp437('░'),
+                fg: rltk::RGB::named(rltk::GRAY),
+                bg: rltk::RGB::named(rltk::BLACK),
+                lifetime: 200.0,
+            },
+            Targets::Tile { tile_idx },
+        );
+
+        // 地形改变了，所有的视野都需要重新计算
+        for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+            viewshed.dirty = true;
+        }
+    }
+}
+
+fn wake_monsters_near(ecs: &mut World, center: Point) {
+    let to_wake: Vec<Entity> = {
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let asleep = ecs.read_storage::<Asleep>();
+        (&entities, &positions, &asleep)
+            .join()
+            .filter(|(_, pos, _)| {
+                rltk::DistanceAlg::Pythagoras.distance2d(center, Point::new(pos.x, pos.y))
+                    <= RELEASE_RADIUS
+            })
+            .map(|(entity, _, _)| entity)
+            .collect()
+    };
+    let mut asleep = ecs.write_storage::<Asleep>();
+    for entity in to_wake.iter() {
+        asleep.remove(*entity);
+    }
+}
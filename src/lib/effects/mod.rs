@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use crate::{Map, MechanismAction};
+use rltk::{Point, RGB};
+use specs::prelude::*;
+
+// 效果系统
+// 物品、陷阱、攻击 都只负责把效果放进队列，效果在这里统一处理
+
+mod curse;
+mod damage;
+mod identify;
+mod items;
+mod mechanisms;
+mod movement;
+mod particles;
+mod recharge;
+mod spells;
+mod targeting;
+mod traps;
+mod triggers;
+
+pub use identify::identify_item;
+pub use targeting::*;
+pub use triggers::consume_item;
+
+// 效果的类型
+pub enum EffectType {
+    Damage {
+        amount: i32,
+    },
+    Healing {
+        amount: i32,
+    },
+    // 施加状态
+    Confusion {
+        turns: i32,
+    },
+    WellFed,
+    TeleportTo {
+        x: i32,
+        y: i32,
+    },
+    RevealMap,
+    // 物品落在地图上的 (x, y)，射偏的弹药 和 扔出去的物品
+    LandItem {
+        x: i32,
+        y: i32,
+    },
+    // 传送到随机的位置
+    RandomTeleport,
+    // 中毒
+    Poison {
+        turns: i32,
+        damage: i32,
+    },
+    // 叫醒范围内的怪物
+    Alarm {
+        radius: i32,
+    },
+    // 掉到下一层
+    TrapDoor,
+    // 召唤怪物
+    Summon {
+        count: i32,
+    },
+    // 机关改变 tile，tile 是目标的坐标
+    Mechanism {
+        action: MechanismAction,
+        tile: Point,
+    },
+    // 打开鉴定物品的菜单
+    Identify {
+        scroll: Entity,
+    },
+    RemoveCurse,
+    // 为背包中的物品恢复充能
+    Recharge,
+    // 学会一个法术
+    LearnSpell {
+        spell: String,
+    },
+    Particle {
+        glyph: rltk::FontCharType,
+        fg: RGB,
+        bg: RGB,
+        lifetime: f32,
+    },
+    // 使用物品，物品上的组件决定产生哪些效果
+    ItemUse {
+        item: Entity,
+    },
+    // 陷阱被触发
+    TriggerFire {
+        trigger: Entity,
+    },
+    // 施放法术，法术上的组件决定产生哪些效果
+    SpellUse {
+        spell: Entity,
+    },
+}
+
+// 效果作用的目标
+#[derive(Clone)]
+pub enum Targets {
+    Single { target: Entity },
+    TargetList { targets: Vec<Entity> },
+    Tile { tile_idx: i32 },
+    Tiles { tiles: Vec<i32> },
+    // 以 center 为中心，radius 为半径的范围
+    Area { center: Point, radius: i32 },
+}
+
+// 一个效果请求
+pub struct EffectSpawner {
+    pub creator: Option<Entity>,
+    pub effect_type: EffectType,
+    pub targets: Targets,
+}
+
+// 效果队列，作为资源插入 world 中
+#[derive(Default)]
+pub struct EffectQueue {
+    requests: VecDeque<EffectSpawner>,
+}
+
+impl EffectQueue {
+    pub fn new() -> EffectQueue {
+        EffectQueue {
+            requests: VecDeque::new(),
+        }
+    }
+
+    // 任何来源都通过这个函数添加效果
+    pub fn add_effect(
+        &mut self,
+        creator: Option<Entity>,
+        effect_type: EffectType,
+        targets: Targets,
+    ) {
+        self.requests.push_back(EffectSpawner {
+            creator,
+            effect_type,
+            targets,
+        });
+    }
+}
+
+// 处理队列中所有的效果，效果本身也可以产生新的效果
+pub fn run_effects_queue(ecs: &mut World) {
+    loop {
+        let effect: Option<EffectSpawner> =
+            ecs.write_resource::<EffectQueue>().requests.pop_front();
+        if let Some(effect) = effect {
+            target_applicator(ecs, &effect);
+        } else {
+            break;
+        }
+    }
+}
+
+// 根据目标的类型，将效果应用到实体或者 tile 上
+fn target_applicator(ecs: &mut World, effect: &EffectSpawner) {
+    // 物品和陷阱自己决定如何处理目标
+    if let EffectType::ItemUse { item } = effect.effect_type {
+        triggers::item_trigger(effect.creator, item, &effect.targets, ecs);
+        return;
+    }
+    if let EffectType::TriggerFire { trigger } = effect.effect_type {
+        triggers::trigger(effect.creator, trigger, &effect.targets, ecs);
+        return;
+    }
+    if let EffectType::SpellUse { spell } = effect.effect_type {
+        triggers::spell_trigger(effect.creator, spell, &effect.targets, ecs);
+        return;
+    }
+
+    match &effect.targets {
+        Targets::Single { target } => affect_entity(ecs, effect, *target),
+        Targets::TargetList { targets } => targets
+            .iter()
+            .for_each(|entity| affect_entity(ecs, effect, *entity)),
+        Targets::Tile { tile_idx } => affect_tile(ecs, effect, *tile_idx),
+        Targets::Tiles { tiles } => tiles
+            .iter()
+            .for_each(|tile_idx| affect_tile(ecs, effect, *tile_idx)),
+        Targets::Area { center, radius } => {
+            let tiles = aoe_tiles(&ecs.fetch::<Map>(), *center, *radius);
+            tiles
+                .iter()
+                .for_each(|tile_idx| affect_tile(ecs, effect, *tile_idx));
+        }
+    }
+}
+
+// 这个效果是否作用于 tile 上的实体
+fn tile_effect_hits_entities(effect: &EffectType) -> bool {
+    matches!(
+        effect,
+        EffectType::Damage { .. }
+            | EffectType::Healing { .. }
+            | EffectType::Confusion { .. }
+            | EffectType::WellFed
+            | EffectType::TeleportTo { .. }
+            | EffectType::RandomTeleport
+            | EffectType::Poison { .. }
+    )
+}
+
+fn affect_tile(ecs: &mut World, effect: &EffectSpawner, tile_idx: i32) {
+    if tile_effect_hits_entities(&effect.effect_type) {
+        let content: Vec<Entity> = ecs.fetch::<Map>().tile_content[tile_idx as usize].clone();
+        content
+            .iter()
+            .for_each(|entity| affect_entity(ecs, effect, *entity));
+    }
+
+    match &effect.effect_type {
+        EffectType::Particle { .. } => particles::particle_to_tile(ecs, tile_idx, effect),
+        EffectType::RevealMap => movement::reveal_map(ecs),
+        EffectType::Mechanism { .. } => mechanisms::activate(ecs, effect, tile_idx),
+        _ => {}
+    }
+}
+
+fn affect_entity(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    match &effect.effect_type {
+        EffectType::Damage { .. } => damage::inflict_damage(ecs, effect, target),
+        EffectType::Healing { .. } => damage::heal_damage(ecs, effect, target),
+        EffectType::Confusion { .. } => damage::add_confusion(ecs, effect, target),
+        EffectType::WellFed => damage::well_fed(ecs, target),
+        EffectType::TeleportTo { .. } => movement::apply_teleport(ecs, effect, target),
+        EffectType::RevealMap => movement::reveal_map(ecs),
+        EffectType::LandItem { .. } => items::land_item(ecs, effect, target),
+        EffectType::RandomTeleport => movement::random_teleport(ecs, target),
+        EffectType::Poison { .. } => damage::add_poison(ecs, effect, target),
+        EffectType::Alarm { .. } => traps::sound_alarm(ecs, effect, target),
+        EffectType::TrapDoor => traps::fall_through(ecs, target),
+        EffectType::Summon { .. } => traps::summon_monsters(ecs, effect, target),
+        EffectType::Identify { .. } => identify::show_identify_menu(ecs, effect, target),
+        EffectType::RemoveCurse => curse::remove_curse(ecs, target),
+        EffectType::Recharge => recharge::recharge_items(ecs, target),
+        EffectType::LearnSpell { .. } => spells::learn_spell(ecs, effect, target),
+        EffectType::Particle { .. } => {
+            if let Some(pos) = entity_position(ecs, target) {
+                particles::particle_to_tile(ecs, pos, effect)
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Map, Position, RunState, TileType, Viewshed};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use super::{EffectSpawner, EffectType};
+
+// 将实体传送到指定的位置
+pub fn apply_teleport(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::TeleportTo { x, y } = effect.effect_type {
+        let player_entity = *ecs.fetch::<Entity>();
+        if let Some(pos) = ecs.write_storage::<Position>().get_mut(target) {
+            pos.x = x;
+            pos.y = y;
+        } else {
+            return;
+        }
+        if let Some(vs) = ecs.write_storage::<Viewshed>().get_mut(target) {
+            vs.dirty = true;
+        }
+        // 传送的是玩家，更新玩家位置的资源
+        if target == player_entity {
+            let mut ppos = ecs.write_resource::<Point>();
+            *ppos = Point::new(x, y);
+        }
+    }
+}
+
+// 传送到地图上随机的一块空地
+pub fn random_teleport(ecs: &mut World, target: Entity) {
+    let destination = {
+        let map = ecs.fetch::<Map>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let mut floors: Vec<(i32, i32)> = Vec::new();
+        for y in 1..map.height - 1 {
+            for x in 1..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] == TileType::Floor && !map.blocked[idx] {
+                    floors.push((x, y));
+                }
+            }
+        }
+        if floors.is_empty() {
+            return;
+        }
+        floors[rng.roll_dice(1, floors.len() as i32) as usize - 1]
+    };
+    let effect = EffectSpawner {
+        creator: None,
+        effect_type: EffectType::TeleportTo {
+            x: destination.0,
+            y: destination.1,
+        },
+        targets: super::Targets::Single { target },
+    };
+    apply_teleport(ecs, &effect, target);
+}
+
+// 揭开全部地图
+pub fn reveal_map(ecs: &mut World) {
+    // 逐行揭开地图的动画 在 MagicMapReveal 状态中进行
+    let mut runstate = ecs.write_resource::<RunState>();
+    *runstate = RunState::MagicMapReveal { row: 0 };
+}
@@ -0,0 +1,26 @@
+use crate::{Map, ParticleBuilder};
+use specs::prelude::*;
+
+use super::{EffectSpawner, EffectType};
+
+// 粒子效果交给 ParticleBuilder 生成
+pub fn particle_to_tile(ecs: &mut World, tile_idx: i32, effect: &EffectSpawner) {
+    if let EffectType::Particle {
+        glyph,
+        fg,
+        bg,
+        lifetime,
+    } = effect.effect_type
+    {
+        let map = ecs.fetch::<Map>();
+        let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+        particle_builder.request(
+            tile_idx % map.width,
+            tile_idx / map.width,
+            fg,
+            bg,
+            glyph,
+            lifetime,
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{Charges, GameLog, InBackpack};
+use specs::prelude::*;
+
+// 目标背包中所有有充能的物品 恢复到最大充能
+pub fn recharge_items(ecs: &mut World, target: Entity) {
+    let mut recharged = false;
+    {
+        let backpack = ecs.read_storage::<InBackpack>();
+        let mut charges = ecs.write_storage::<Charges>();
+        for (pack, charges) in (&backpack, &mut charges).join() {
+            if pack.owner == target && charges.current < charges.max {
+                charges.current = charges.max;
+                recharged = true;
+            }
+        }
+    }
+
+    if target == *ecs.fetch::<Entity>() {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        if recharged {
+            gamelog.push("Your items hum with renewed power.");
+        } else {
+            gamelog.push("You feel a faint tingle, but nothing happens.");
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use specs::prelude::*;
+
+use super::{EffectSpawner, EffectType};
+use crate::{spawner, GameLog, KnownSpell, Name};
+
+// 阅读法术书，学会一个新的法术
+pub fn learn_spell(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::LearnSpell { spell } = &effect.effect_type {
+        let already_known = {
+            let known = ecs.read_storage::<KnownSpell>();
+            let names = ecs.read_storage::<Name>();
+            (&known, &names)
+                .join()
+                .any(|(k, n)| k.owner == target && n.name == *spell)
+        };
+
+        let is_player = target == *ecs.fetch::<Entity>();
+        if already_known {
+            if is_player {
+                ecs.fetch_mut::<GameLog>()
+                    .push(format!("You already know {}.", spell));
+            }
+            return;
+        }
+
+        spawner::spell(ecs, spell, target);
+        if is_player {
+            ecs.fetch_mut::<GameLog>()
+                .push(format!("You learn the spell {}!", spell));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{Map, Position};
+use rltk::Point;
+use specs::prelude::*;
+
+use super::Targets;
+
+// 实体所在的 tile 索引
+pub fn entity_position(ecs: &World, target: Entity) -> Option<i32> {
+    if let Some(pos) = ecs.read_storage::<Position>().get(target) {
+        let map = ecs.fetch::<Map>();
+        return Some(map.xy_idx(pos.x, pos.y) as i32);
+    }
+    None
+}
+
+// AoE 爆炸的范围，只保留地图边界内的 tile
+pub fn aoe_tiles(map: &Map, target: Point, radius: i32) -> Vec<i32> {
+    let mut blast_tiles = rltk::field_of_view(target, radius, map);
+    blast_tiles.retain(|p| p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1);
+    let mut result = Vec::new();
+    for t in blast_tiles.iter() {
+        result.push(map.xy_idx(t.x, t.y) as i32);
+    }
+    result
+}
+
+// 目标 tile 列表
+pub fn target_tiles(ecs: &World, targets: &Targets) -> Vec<i32> {
+    match targets {
+        Targets::Single { target } => entity_position(ecs, *target).into_iter().collect(),
+        Targets::TargetList { targets } => targets
+            .iter()
+            .filter_map(|e| entity_position(ecs, *e))
+            .collect(),
+        Targets::Tile { tile_idx } => vec![*tile_idx],
+        Targets::Tiles { tiles } => tiles.clone(),
+        Targets::Area { center, radius } => aoe_tiles(&ecs.fetch::<Map>(), *center, *radius),
+    }
+}
+
+// 目标中所有的实体
+pub fn target_entities(ecs: &World, targets: &Targets) -> Vec<Entity> {
+    match targets {
+        Targets::Single { target } => vec![*target],
+        Targets::TargetList { targets } => targets.clone(),
+        _ => {
+            let map = ecs.fetch::<Map>();
+            let mut result = Vec::new();
+            for tile_idx in target_tiles(ecs, targets).iter() {
+                for entity in map.tile_content[*tile_idx as usize].iter() {
+                    result.push(*entity);
+                }
+            }
+            result
+        }
+    }
+}
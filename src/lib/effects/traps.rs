@@ -0,0 +1,96 @@
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use super::{EffectSpawner, EffectType};
+use crate::{spawner, Asleep, GameLog, Map, Monster, Name, Position, RunState};
+
+// 警报响起，叫醒范围内所有睡着的怪物
+pub fn sound_alarm(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Alarm { radius } = effect.effect_type {
+        let center = match ecs.read_storage::<Position>().get(target) {
+            Some(pos) => Point::new(pos.x, pos.y),
+            None => return,
+        };
+        let mut to_wake: Vec<Entity> = Vec::new();
+        {
+            let entities = ecs.entities();
+            let positions = ecs.read_storage::<Position>();
+            let asleep = ecs.read_storage::<Asleep>();
+            for (entity, pos, _asleep) in (&entities, &positions, &asleep).join() {
+                let distance =
+                    rltk::DistanceAlg::Pythagoras.distance2d(center, Point::new(pos.x, pos.y));
+                if distance <= radius as f32 {
+                    to_wake.push(entity);
+                }
+            }
+        }
+        let mut asleep = ecs.write_storage::<Asleep>();
+        for entity in to_wake.iter() {
+            asleep.remove(*entity);
+        }
+        ecs.fetch_mut::<GameLog>().push("A loud alarm rings out!");
+    }
+}
+
+// 掉进陷坑，玩家去到下一层，怪物直接消失
+pub fn fall_through(ecs: &mut World, target: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    if target == player_entity {
+        ecs.fetch_mut::<GameLog>()
+            .push("You fall through a pit to the level below!");
+        *ecs.write_resource::<RunState>() = RunState::NextLevel;
+        return;
+    }
+    if ecs.read_storage::<Monster>().get(target).is_none() {
+        return;
+    }
+    if let Some(name) = ecs.read_storage::<Name>().get(target) {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("{} falls into a pit!", name.name));
+    }
+    ecs.entities().delete(target).expect("Unable to delete");
+}
+
+// 在目标周围的空地上 召唤怪物
+pub fn summon_monsters(ecs: &mut World, effect: &EffectSpawner, target: Entity) {
+    if let EffectType::Summon { count } = effect.effect_type {
+        let center = match ecs.read_storage::<Position>().get(target) {
+            Some(pos) => Point::new(pos.x, pos.y),
+            None => return,
+        };
+        let mut spots: Vec<(i32, i32, &str)> = Vec::new();
+        {
+            let map = ecs.fetch::<Map>();
+            let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+            for y in center.y - 1..=center.y + 1 {
+                for x in center.x - 1..=center.x + 1 {
+                    if spots.len() as i32 >= count
+                        || x < 1
+                        || x >= map.width - 1
+                        || y < 1
+                        || y >= map.height - 1
+                    {
+                        continue;
+                    }
+                    let idx = map.xy_idx(x, y);
+                    if !map.blocked[idx] && map.tile_content[idx].is_empty() {
+                        let name = if rng.roll_dice(1, 2) == 1 {
+                            "Goblin"
+                        } else {
+                            "Orc"
+                        };
+                        spots.push((x, y, name));
+                    }
+                }
+            }
+        }
+        if spots.is_empty() {
+            return;
+        }
+        for (x, y, name) in spots.iter() {
+            spawner::spawn_named(ecs, *x, *y, name);
+        }
+        ecs.fetch_mut::<GameLog>()
+            .push("Monsters appear out of thin air!");
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    Alarm, AreaOfEffect, Charges, CombatStats, Confusion, Consumable, GameLog, Hidden, InBackpack,
+    InflictsDamage, InflictsPoison, Item, ItemRegistry, Lever, MagicMapper, Map, Mechanism,
+    MechanismAction, Name, Potion, ProvidesFood, ProvidesHealing, ProvidesIdentification,
+    ProvidesRecharge, ProvidesRemoveCurse, Quantity, RandomTeleport, Renderable, SingleActivation,
+    Spell, SummonsMonsters, TeachesSpell, TrapDoor,
+};
+use specs::prelude::*;
+
+use super::{target_entities, EffectQueue, EffectType, Targets};
+
+// 使用物品，消耗品使用后删除
+pub fn item_trigger(creator: Option<Entity>, item: Entity, targets: &Targets, ecs: &mut World) {
+    // 玩家使用过的物品就被鉴定了
+    if creator == Some(*ecs.fetch::<Entity>()) {
+        if let Some(name) = ecs.read_storage::<Name>().get(item) {
+            ecs.write_resource::<ItemRegistry>().identify(&name.name);
+        }
+    }
+
+    let did_something = event_trigger(creator, item, targets, ecs);
+    // 扔出去的药水 不在背包里，摔碎了 就没有了
+    let shattered = ecs.read_storage::<Potion>().get(item).is_some()
+        && ecs.read_storage::<InBackpack>().get(item).is_none();
+    if !did_something && !shattered {
+        return;
+    }
+
+    // 有充能的物品 使用一次 减少一次充能，用完之后 消耗品 会碎掉
+    let mut destroy = shattered || ecs.read_storage::<Consumable>().get(item).is_some();
+    if let Some(charges) = ecs.write_storage::<Charges>().get_mut(item) {
+        charges.current -= 1;
+        destroy = destroy && charges.current < 1;
+        if destroy && creator == Some(*ecs.fetch::<Entity>()) {
+            if let Some(name) = ecs.read_storage::<Name>().get(item) {
+                ecs.fetch_mut::<GameLog>()
+                    .push(format!("{} crumbles to dust.", name.name));
+            }
+        }
+    }
+    if destroy {
+        consume_item(ecs, item);
+    }
+}
+
+// 消耗一个物品，成堆的物品 只消耗其中的一个
+pub fn consume_item(ecs: &mut World, item: Entity) {
+    if let Some(quantity) = ecs.write_storage::<Quantity>().get_mut(item) {
+        if quantity.amount > 1 {
+            quantity.amount -= 1;
+            return;
+        }
+    }
+    ecs.entities().delete(item).expect("Delete failed");
+}
+
+// 触发陷阱，只触发一次的陷阱触发后删除
+pub fn trigger(creator: Option<Entity>, trigger: Entity, targets: &Targets, ecs: &mut World) {
+    // The trap is no longer hidden
+    ecs.write_storage::<Hidden>().remove(trigger);
+
+    // 拉杆 在两个位置之间切换
+    if let Some(lever) = ecs.write_storage::<Lever>().get_mut(trigger) {
+        lever.pulled = !lever.pulled;
+        if let Some(render) = ecs.write_storage::<Renderable>().get_mut(trigger) {
+            render.glyph = rltk::to_cp437(if lever.pulled { '\\' } else { '/' });
+        }
+        ecs.fetch_mut::<GameLog>().push("You pull the lever.");
+    }
+
+    let did_something = event_trigger(creator, trigger, targets, ecs);
+
+    if did_something
+        && ecs
+            .read_storage::<SingleActivation>()
+            .get(trigger)
+            .is_some()
+    {
+        ecs.entities()
+            .delete(trigger)
+            .expect("Unable to delete trap");
+    }
+}
+
+// 施放法术，法术不会被消耗
+pub fn spell_trigger(creator: Option<Entity>, spell: Entity, targets: &Targets, ecs: &mut World) {
+    event_trigger(creator, spell, targets, ecs);
+}
+
+// 根据实体上的组件，将对应的效果加入队列
+// 物品 和 陷阱 共用这一套逻辑，新的物品只需要声明它有哪些组件
+fn event_trigger(
+    creator: Option<Entity>,
+    entity: Entity,
+    targets: &Targets,
+    ecs: &mut World,
+) -> bool {
+    let mut did_something = false;
+    let mut add_effects: Vec<(EffectType, Targets)> = Vec::new();
+    let mut log_entries: Vec<String> = Vec::new();
+    {
+        let player_entity = ecs.fetch::<Entity>();
+        // 只有玩家使用物品 或者施法时才打印日志
+        let log_it = creator == Some(*player_entity)
+            && (ecs.read_storage::<Item>().get(entity).is_some()
+                || ecs.read_storage::<Spell>().get(entity).is_some());
+        let names = ecs.read_storage::<Name>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let item_name = names
+            .get(entity)
+            .map(|n| n.name.clone())
+            .unwrap_or_default();
+        // 目标中可以受到伤害的实体
+        let victims: Vec<Entity> = target_entities(ecs, targets)
+            .into_iter()
+            .filter(|e| combat_stats.get(*e).is_some())
+            .collect();
+
+        // 范围攻击，显示攻击的范围
+        if ecs.read_storage::<AreaOfEffect>().get(entity).is_some() {
+            add_effects.push((
+                EffectType::Particle {
+                    glyph: rltk::to_cp437('░'),
+                    fg: rltk::RGB::named(rltk::ORANGE),
+                    bg: rltk::RGB::named(rltk::BLACK),
+                    lifetime: 200.0,
+                },
+                targets.clone(),
+            ));
+        }
+
+        // it is edible, eat it
+        if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
+            add_effects.push((EffectType::WellFed, targets.clone()));
+            if log_it {
+                log_entries.push(format!("You eat the {}.", item_name));
+            }
+            did_something = true;
+        }
+
+        // if its a magic mapper...
+        if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
+            add_effects.push((EffectType::RevealMap, targets.clone()));
+            if log_it {
+                log_entries.push("The map is revealed to you!".to_string());
+            }
+            did_something = true;
+        }
+
+        // 鉴定卷轴，选择要鉴定的物品，鉴定之后 卷轴才会被消耗
+        if ecs
+            .read_storage::<ProvidesIdentification>()
+            .get(entity)
+            .is_some()
+        {
+            add_effects.push((EffectType::Identify { scroll: entity }, targets.clone()));
+            if log_it {
+                log_entries.push("Choose an item to identify.".to_string());
+            }
+        }
+
+        // 解除诅咒
+        if ecs
+            .read_storage::<ProvidesRemoveCurse>()
+            .get(entity)
+            .is_some()
+        {
+            add_effects.push((EffectType::RemoveCurse, targets.clone()));
+            did_something = true;
+        }
+
+        // 法术书
+        if let Some(teaches) = ecs.read_storage::<TeachesSpell>().get(entity) {
+            add_effects.push((
+                EffectType::LearnSpell {
+                    spell: teaches.spell.clone(),
+                },
+                targets.clone(),
+            ));
+            did_something = true;
+        }
+
+        // 充能卷轴
+        if ecs.read_storage::<ProvidesRecharge>().get(entity).is_some() {
+            add_effects.push((EffectType::Recharge, targets.clone()));
+            did_something = true;
+        }
+
+        // if it heals, apply the healing
+        if let Some(heal) = ecs.read_storage::<ProvidesHealing>().get(entity) {
+            add_effects.push((
+                EffectType::Healing {
+                    amount: heal.heal_amount,
+                },
+                targets.clone(),
+            ));
+            if log_it {
+                // 能吃的东西 上面已经说过吃掉了
+                if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
+                    log_entries.push(format!("It restores {} hp.", heal.heal_amount));
+                } else if ecs.read_storage::<Spell>().get(entity).is_some() {
+                    log_entries.push(format!("You are healed for {} hp.", heal.heal_amount));
+                } else {
+                    log_entries.push(format!(
+                        "You drink the {}, healing {} hp.",
+                        item_name, heal.heal_amount
+                    ));
+                }
+            }
+            did_something = true;
+        }
+
+        // if it inflicts damage, apply it to the targets
+        // 目标里没有可以受伤的实体，就什么都没有发生，物品不会被消耗
+        if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
+            if victims.is_empty() {
+                if log_it {
+                    log_entries.push(format!("The {} hits nothing.", item_name));
+                }
+            } else {
+                add_effects.push((
+                    EffectType::Damage {
+                        amount: damage.damage,
+                    },
+                    targets.clone(),
+                ));
+                if log_it {
+                    for mob in victims.iter() {
+                        if let Some(mob_name) = names.get(*mob) {
+                            log_entries.push(format!(
+                                "You use {} on {}, inflicting {} hp.",
+                                item_name, mob_name.name, damage.damage
+                            ));
+                        }
+                    }
+                }
+                did_something = true;
+            }
+        }
+
+        // 中毒
+        if let Some(poison) = ecs.read_storage::<InflictsPoison>().get(entity) {
+            add_effects.push((
+                EffectType::Poison {
+                    turns: poison.turns,
+                    damage: poison.damage,
+                },
+                targets.clone(),
+            ));
+            did_something = true;
+        }
+
+        // 陷阱的效果
+        if ecs.read_storage::<RandomTeleport>().get(entity).is_some() {
+            add_effects.push((EffectType::RandomTeleport, targets.clone()));
+            did_something = true;
+        }
+        if let Some(alarm) = ecs.read_storage::<Alarm>().get(entity) {
+            add_effects.push((
+                EffectType::Alarm {
+                    radius: alarm.radius,
+                },
+                targets.clone(),
+            ));
+            did_something = true;
+        }
+        if ecs.read_storage::<TrapDoor>().get(entity).is_some() {
+            add_effects.push((EffectType::TrapDoor, targets.clone()));
+            did_something = true;
+        }
+        if let Some(summon) = ecs.read_storage::<SummonsMonsters>().get(entity) {
+            add_effects.push((
+                EffectType::Summon {
+                    count: summon.count,
+                },
+                targets.clone(),
+            ));
+            did_something = true;
+        }
+
+        // 远处的机关
+        if let Some(mechanism) = ecs.read_storage::<Mechanism>().get(entity) {
+            let map = ecs.fetch::<Map>();
+            for pt in mechanism.targets.iter() {
+                add_effects.push((
+                    EffectType::Mechanism {
+                        action: mechanism.action,
+                        tile: *pt,
+                    },
+                    Targets::Tile {
+                        tile_idx: map.xy_idx(pt.x, pt.y) as i32,
+                    },
+                ));
+            }
+            log_entries.push(
+                match mechanism.action {
+                    MechanismAction::ToggleDoors => "You hear a door grind on its hinges.",
+                    MechanismAction::ReleaseMonsters => {
+                        "The gates rumble open, releasing what was inside!"
+                    }
+                    MechanismAction::CollapseWalls => "The walls collapse with a roar!",
+                    MechanismAction::Flood => "Water rushes into the room!",
+                }
+                .to_string(),
+            );
+            did_something = true;
+        }
+
+        // Can it pass along confusion?
+        if let Some(confusion) = ecs.read_storage::<Confusion>().get(entity) {
+            add_effects.push((
+                EffectType::Confusion {
+                    turns: confusion.turns,
+                },
+                targets.clone(),
+            ));
+            if log_it {
+                for mob in victims.iter() {
+                    if let Some(mob_name) = names.get(*mob) {
+                        log_entries.push(format!(
+                            "You use {} on {}, confusing them.",
+                            item_name, mob_name.name
+                        ));
+                    }
+                }
+            }
+            did_something = true;
+        }
+    }
+
+    let mut effects = ecs.write_resource::<EffectQueue>();
+    for (effect_type, targets) in add_effects {
+        effects.add_effect(creator, effect_type, targets);
+    }
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.extend(log_entries);
+
+    did_something
+}
@@ -0,0 +1,81 @@
+use specs::prelude::*;
+
+use crate::{AttributeBonus, Attributes, Burden, Equipped, GameLog, InBackpack, Quantity, Weight};
+
+// 负重系统
+// 统计每个有属性的实体 背包和身上装备的总重量，超过负重上限后 战斗会受到惩罚
+
+// 每一点力量 可以背负的重量
+const CAPACITY_PER_STRENGTH: f32 = 3.0;
+// 负重上限的倍数，超过这个重量就拿不动了
+pub const MAX_CARRY_MULTIPLIER: f32 = 2.0;
+// 负重过多时 攻击和防御的惩罚
+pub const ENCUMBRANCE_PENALTY: i32 = 2;
+
+pub fn carry_capacity(strength: i32) -> f32 {
+    strength as f32 * CAPACITY_PER_STRENGTH
+}
+
+pub struct EncumbranceSystem {}
+
+impl<'a> System<'a> for EncumbranceSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Burden>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, Quantity>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, AttributeBonus>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            attributes,
+            mut burdens,
+            weights,
+            quantities,
+            backpack,
+            equipped,
+            attribute_bonuses,
+        ) = data;
+
+        for (entity, attr, burden) in (&entities, &attributes, &mut burdens).join() {
+            let was_encumbered = burden.is_encumbered();
+
+            let mut carried = 0.0;
+            for (item, weight) in (&entities, &weights).join() {
+                let owned = backpack.get(item).is_some_and(|b| b.owner == entity)
+                    || equipped.get(item).is_some_and(|e| e.owner == entity);
+                if owned {
+                    let amount = quantities.get(item).map_or(1, |q| q.amount);
+                    carried += weight.weight * amount as f32;
+                }
+            }
+            burden.carried = carried;
+            // 装备增加的力量 也会提高负重上限
+            let mut strength = attr.strength;
+            for (bonus, equipped_by) in (&attribute_bonuses, &equipped).join() {
+                if equipped_by.owner == entity {
+                    strength += bonus.strength;
+                }
+            }
+            burden.capacity = carry_capacity(strength);
+
+            if entity == *player_entity && was_encumbered != burden.is_encumbered() {
+                if burden.is_encumbered() {
+                    gamelog.push("You are overburdened, and struggle to fight!");
+                } else {
+                    gamelog.push("You are no longer overburdened.");
+                }
+            }
+        }
+    }
+}
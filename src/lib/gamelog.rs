@@ -1,7 +1,158 @@
+use std::collections::VecDeque;
+
+use rltk::RGB;
+use serde::{Deserialize, Serialize};
+
 // 游戏的日志
-// 游戏作为一种资源
+// 游戏作为一种资源，随游戏一起保存
+
+// 日志最多保留的条数，更早的日志被丢弃
+pub const MAX_LOG_ENTRIES: usize = 200;
+
+// 一段带颜色的文字
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogFragment {
+    pub color: RGB,
+    pub text: String,
+    // 伤害之类的数值
+    #[serde(default)]
+    pub value: bool,
+}
+
+// 日志的模板，数值用 # 代替
+fn template(fragments: &[LogFragment]) -> String {
+    fragments
+        .iter()
+        .map(|f| if f.value { "#" } else { f.text.as_str() })
+        .collect()
+}
+
+// 一条日志，由带颜色的片段组成
+// 连续重复的日志 合并为一条并记录次数，只有数值不同的日志 也算重复，显示最后一次的数值
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    // 发生在第几回合
+    pub turn: i32,
+    pub fragments: Vec<LogFragment>,
+    pub count: i32,
+    // 次要的日志，简略模式下 不显示
+    #[serde(default)]
+    pub minor: bool,
+}
+
+impl LogEntry {
+    // 不带颜色的全文
+    pub fn text(&self) -> String {
+        self.fragments.iter().map(|f| f.text.as_str()).collect()
+    }
+}
 
 // 对日志进行建模
-pub struct GameLog{
-    pub entries:Vec<String>
-}
\ No newline at end of file
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GameLog {
+    pub entries: VecDeque<LogEntry>,
+    // 当前的回合数
+    pub turn: i32,
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog {
+            entries: VecDeque::new(),
+            turn: 0,
+        }
+    }
+
+    // 白色的日志
+    pub fn push<S: ToString>(&mut self, text: S) {
+        self.add(vec![LogFragment {
+            color: RGB::named(rltk::WHITE),
+            text: text.to_string(),
+            value: false,
+        }]);
+    }
+
+    pub fn extend<I: IntoIterator<Item = String>>(&mut self, texts: I) {
+        for text in texts {
+            self.push(text);
+        }
+    }
+
+    pub fn add(&mut self, fragments: Vec<LogFragment>) {
+        self.add_entry(fragments, false);
+    }
+
+    // 和上一条日志的模板相同时 只增加次数
+    fn add_entry(&mut self, fragments: Vec<LogFragment>, minor: bool) {
+        let turn = self.turn;
+        if let Some(last) = self.entries.back_mut() {
+            if template(&last.fragments) == template(&fragments) {
+                last.count += 1;
+                last.turn = turn;
+                last.fragments = fragments;
+                return;
+            }
+        }
+        self.entries.push_back(LogEntry {
+            turn,
+            fragments,
+            count: 1,
+            minor,
+        });
+        while self.entries.len() > MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    // 一段一段地拼出 带颜色的日志
+    pub fn entry(&mut self) -> LogBuilder<'_> {
+        LogBuilder {
+            log: self,
+            fragments: Vec::new(),
+            minor: false,
+        }
+    }
+
+    // 要显示的日志，简略模式下 跳过次要的日志
+    pub fn shown(&self, brief: bool) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| !brief || !entry.minor)
+    }
+}
+
+pub struct LogBuilder<'a> {
+    log: &'a mut GameLog,
+    fragments: Vec<LogFragment>,
+    minor: bool,
+}
+
+impl<'a> LogBuilder<'a> {
+    pub fn text<S: ToString>(self, text: S) -> Self {
+        self.colored(text, RGB::named(rltk::WHITE))
+    }
+
+    pub fn colored<S: ToString>(self, text: S, color: RGB) -> Self {
+        self.fragment(text.to_string(), color, false)
+    }
+
+    // 伤害的数值 显示为红色
+    pub fn damage(self, amount: i32) -> Self {
+        self.fragment(amount.to_string(), RGB::named(rltk::RED), true)
+    }
+
+    fn fragment(mut self, text: String, color: RGB, value: bool) -> Self {
+        self.fragments.push(LogFragment { color, text, value });
+        self
+    }
+
+    // 没有命中之类的 次要日志
+    pub fn minor(mut self) -> Self {
+        self.minor = true;
+        self
+    }
+
+    pub fn log(self) {
+        self.log.add_entry(self.fragments, self.minor);
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{
-    Equipped, GameLog, Hidden, HungerClock, HungerState, InBackpack, Map, Name, Position,
-    RexAssets, RunState, State, Viewshed,
+    can_craft, damage_type_name, item_display_name, key_name, projectile_path, sell_price,
+    tile_description, Action, Asleep, AttributeBonus, Attributes, Burden, Charges, ConfuseOnHit,
+    Confusion, DefenseBonus, Description, ElementalDamage, EquipmentSlot, Equippable, Equipped,
+    GameLog, Hidden, HungerClock, HungerState, InBackpack, Item, ItemRegistry, ItemValue, KeyMap,
+    KillCount, KnownSpell, LifeSteal, LogEntry, Mana, Map, MeleePowerBonus, Monster, Name,
+    ObfuscatedName, Poisoned, Position, Potion, Quantity, RangedWeapon, RexAssets, RunState,
+    Settings, Spell, State, TwoHanded, Verbosity, Viewshed, Wallet, KEYMAP_FILE, RECIPES,
 };
 
 use super::{CombatStats, Player};
@@ -42,7 +47,7 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         ctx.draw_bar_horizontal(
             28,
             43,
-            51,
+            22,
             stats.hp,
             stats.max_hp,
             RGB::named(rltk::RED),
@@ -74,14 +79,30 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
             ),
         }
     }
+    // 法力值 显示在生命值的右边
+    for (_player, mana) in (&players, &ecs.read_storage::<Mana>()).join() {
+        ctx.print_color(
+            51,
+            43,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            format!(" MP: {} / {} ", mana.current, mana.max),
+        );
+        ctx.draw_bar_horizontal(
+            65,
+            43,
+            13,
+            mana.current,
+            mana.max,
+            RGB::named(rltk::BLUE),
+            RGB::named(rltk::BLACK),
+        );
+    }
     // 在 ui 中打印日志 在UI中显示日志
     let log = ecs.fetch::<GameLog>();
-    let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
-        }
-        y += 1;
+    let brief = ecs.fetch::<Settings>().brief();
+    for (y, entry) in (44..49).zip(log.shown(brief).rev()) {
+        print_log_entry(ctx, 2, y, entry);
     }
     // 在MeleeCombatSystem 混战系统中打印 攻击日志
     // in delete_the_dead 中 打印 死亡日志
@@ -104,14 +125,61 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         RGB::named(rltk::BLACK),
         &depth,
     );
+    // 显示 玩家的金币
+    let player_entity = ecs.fetch::<Entity>();
+    if let Some(wallet) = ecs.read_storage::<Wallet>().get(*player_entity) {
+        ctx.print_color(
+            2,
+            49,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            format!("Gold: {}", wallet.gold),
+        );
+    }
+}
+
+// 按片段的颜色 打印一条日志，重复的日志 显示次数
+fn print_log_entry(ctx: &mut Rltk, x: i32, y: i32, entry: &LogEntry) {
+    let mut x = x;
+    for fragment in entry.fragments.iter() {
+        ctx.print_color(
+            x,
+            y,
+            fragment.color,
+            RGB::named(rltk::BLACK),
+            &fragment.text,
+        );
+        x += fragment.text.len() as i32;
+    }
+    if entry.count > 1 {
+        ctx.print_color(
+            x,
+            y,
+            RGB::named(rltk::GREY),
+            RGB::named(rltk::BLACK),
+            format!(" x{}", entry.count),
+        );
+    }
+}
+
+// 成堆的物品 在名字后面显示数量
+fn with_quantity(name: String, quantity: Option<&Quantity>) -> String {
+    match quantity {
+        Some(quantity) if quantity.amount > 1 => format!("{} (x{})", name, quantity.amount),
+        _ => name,
+    }
 }
 
 // 画出工具提示
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
+    let obfuscated = ecs.read_storage::<ObfuscatedName>();
+    let registry = ecs.fetch::<ItemRegistry>();
+    let quantities = ecs.read_storage::<Quantity>();
     let positions = ecs.read_storage::<Position>();
     let hidden = ecs.read_storage::<Hidden>();
+    let entities = ecs.entities();
 
     let mouse_pos = ctx.mouse_pos();
     if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
@@ -121,13 +189,16 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let mut tooltip: Vec<String> = Vec::new();
 
     // 有name 和 position 的实体都可以 显示 提示
-    for (name, position, _hidden) in (&names, &positions, !&hidden).join() {
+    for (entity, name, position, _hidden) in (&entities, &names, &positions, !&hidden).join() {
         // 将实体的 position 坐标 变为 索引 idx
         let idx = map.xy_idx(position.x, position.y);
         // 如果实体的位置和鼠标的位置相同，说明鼠标点击了实体，并且实体在mao 上是可见的
         if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
-            // 把实体的名字记录在工具提示中
-            tooltip.push(name.name.to_string());
+            // 把实体的名字记录在工具提示中, 未鉴定的物品显示外观名字
+            tooltip.push(with_quantity(
+                item_display_name(name, obfuscated.get(entity), &registry),
+                quantities.get(entity),
+            ));
         }
     }
 
@@ -224,6 +295,10 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let charges = gs.ecs.read_storage::<Charges>();
+    let quantities = gs.ecs.read_storage::<Quantity>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
 
@@ -251,6 +326,23 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         RGB::named(rltk::BLACK),
         "Inventory",
     );
+
+    // 背包的总重量 和 负重上限，负重过多时显示为红色
+    if let Some(burden) = gs.ecs.read_storage::<Burden>().get(*player_entity) {
+        let weight = format!("{:.1}/{:.0} lbs", burden.carried, burden.capacity);
+        let fg = if burden.is_encumbered() {
+            RGB::named(rltk::RED)
+        } else {
+            RGB::named(rltk::YELLOW)
+        };
+        ctx.print_color(
+            44 - weight.len() as i32,
+            y - 2,
+            fg,
+            RGB::named(rltk::BLACK),
+            &weight,
+        );
+    }
     ctx.print_color(
         18,
         y + count as i32 + 1,
@@ -293,7 +385,15 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         );
 
         // 物品的名字  j 是 物品的序号 y 是物品渲染的位置
-        ctx.print(21, y, &name.name.to_string());
+        // 有充能的物品 显示剩余的充能次数
+        let mut item_name = with_quantity(
+            item_display_name(name, obfuscated.get(entity), &registry),
+            quantities.get(entity),
+        );
+        if let Some(charges) = charges.get(entity) {
+            item_name = format!("{} ({}/{})", item_name, charges.current, charges.max);
+        }
+        ctx.print(21, y, &item_name);
         equippable.push(entity);
 
         y += 1;
@@ -326,6 +426,9 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
 pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let quantities = gs.ecs.read_storage::<Quantity>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
 
@@ -388,7 +491,14 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(
+            21,
+            y,
+            &with_quantity(
+                item_display_name(name, obfuscated.get(entity), &registry),
+                quantities.get(entity),
+            ),
+        );
         equippable.push(entity);
         y += 1;
         j += 1;
@@ -419,6 +529,8 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
 pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
     let backpack = gs.ecs.read_storage::<Equipped>();
     let entities = gs.ecs.entities();
     // 得到玩家的装备
@@ -481,7 +593,11 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(
+            21,
+            y,
+            &item_display_name(name, obfuscated.get(entity), &registry),
+        );
         // 如果装备的所有者是玩家，放进equippable 列表
         equippable.push(entity);
         y += 1;
@@ -506,179 +622,1225 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Opti
         },
     }
 }
-// 绘制攻击菜单
-// 返回 菜单 的 状态，和 选项 所在位置
-pub fn ranged_target(
-    gs: &mut State,
-    ctx: &mut Rltk,
-    range: i32,
-) -> (ItemMenuResult, Option<Point>) {
-    // start by obtaining the player's location and viewshed, and it
+// 鉴定物品的菜单，列出玩家 背包中 和 装备着的 未鉴定物品
+pub fn identify_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
-    let player_pos = gs.ecs.fetch::<Point>();
-    let viewsheds = gs.ecs.read_storage::<Viewshed>();
+    let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let entities = gs.ecs.entities();
 
-    // 选中 选项 所在的颜色
+    // 可以鉴定的物品
+    let mut identifiable: Vec<(Entity, String)> = Vec::new();
+    for (entity, name, _obfuscated) in (&entities, &names, &obfuscated).join() {
+        let owned_by_player = backpack
+            .get(entity)
+            .is_some_and(|b| b.owner == *player_entity)
+            || equipped
+                .get(entity)
+                .is_some_and(|e| e.owner == *player_entity);
+        if owned_by_player && !registry.is_identified(&name.name) {
+            identifiable.push((
+                entity,
+                item_display_name(name, obfuscated.get(entity), &registry),
+            ));
+        }
+    }
+    let count = identifiable.len();
+
+    let mut y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
     ctx.print_color(
-        5,
-        0,
+        18,
+        y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Select Target:",
+        "Identify Which Item?",
+    );
+    ctx.print_color(
+        18,
+        y + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
     );
 
-    // Highlight available target cells
-    let mut available_cells = Vec::new();
-    let visible = viewsheds.get(*player_entity);
-    if let Some(visible) = visible {
-        // we have a viewshed 遍历视域范围内的物体,将视域中cell 设置为蓝色
-        for idx in visible.visible_tiles.iter() {
-            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
-            if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
-                available_cells.push(idx);
-            }
-        }
-    } else {
-        return (ItemMenuResult::Cancel, None);
-    }
-
-    // Draw mouse cursor
-    let mouse_pos = ctx.mouse_pos();
+    for (j, (_entity, name)) in identifiable.iter().enumerate() {
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
 
-    let mut valid_target = false;
-    // 如果 鼠标指向有效目标，高亮为青色
-    for idx in available_cells.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
-            valid_target = true;
-        }
+        ctx.print(21, y, name);
+        y += 1;
     }
 
-    // 对于有效的目标，设置背景
-    if valid_target {
-        ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
-        if ctx.left_click {
-            // If you click a valid cell, it returns targeting information for where you are aiming - otherwise, it cancels
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
-        }
-    } else {
-        ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
-        if ctx.left_click {
-            return (ItemMenuResult::Cancel, None);
-        }
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (
+                        ItemMenuResult::Selected,
+                        Some(identifiable[selection as usize].0),
+                    );
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
     }
-
-    (ItemMenuResult::NoResponse, None)
 }
 
-// --------------------------------- main menu-------------------------------
+// ------------------------------------ vendor ----------------------------
+// 交易界面 是买还是卖
 #[derive(PartialEq, Copy, Clone, Debug)]
-pub enum MainMenuSelection {
-    NewGame,
-    LoadGame,
-    Quit,
+pub enum VendorMode {
+    Buy,
+    Sell,
 }
+
 #[derive(PartialEq, Copy, Clone)]
-pub enum MainMenuResult {
-    NoSelection { selected: MainMenuSelection },
-    Selected { selected: MainMenuSelection },
+pub enum VendorResult {
+    NoResponse,
+    Cancel,
+    Selected,
+    SwitchMode,
 }
 
-// 绘制主菜单
-pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
-    // 检查 游戏状态保存文件是否存在
-    let save_exists = super::saveload_system::does_save_exist();
-    let runstate = gs.ecs.fetch::<RunState>();
-
-    // 渲染主菜单背景图片
-    let assets = gs.ecs.fetch::<RexAssets>();
-    ctx.render_xp_sprite(&assets.menu, 0, 0);
-
-    // 从 State 得到现在所处的运行状态，如果当前的状态是 MainMenu, 显示主菜单
-    // add a box for the menu and text.
-    ctx.draw_box_double(
-        24,
-        18,
-        31,
-        10,
-        RGB::named(rltk::WHEAT),
+// 和背包菜单相同的布局：框 标题 带序号的物品 和 底部的提示
+fn draw_item_menu(ctx: &mut Rltk, title: &str, footer: &str, width: i32, items: &[String]) {
+    let count = items.len() as i32;
+    let y = 25 - (count / 2);
+    ctx.draw_box(
+        15,
+        y - 2,
+        width,
+        count + 3,
+        RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
-    ctx.print_color_centered(
-        20,
+    ctx.print_color(
+        18,
+        y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Rust Roguelike Tutorial",
-    );
-    ctx.print_color_centered(
-        21,
-        RGB::named(rltk::CYAN),
-        RGB::named(rltk::BLACK),
-        "by Herbert Wolverson",
+        title,
     );
-    ctx.print_color_centered(
-        22,
-        RGB::named(rltk::GRAY),
+    ctx.print_color(
+        18,
+        y + count + 1,
+        RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Use Up/Down Arrows and Enter",
+        footer,
     );
 
-    // 固定菜单选项的间距
-    let mut y = 24;
-    //  selection 选中哪个选项
-    if let RunState::MainMenu {
-        menu_selection: selection,
-    } = *runstate
+    for (j, item) in items.iter().enumerate() {
+        let row = y + j as i32;
+        ctx.set(
+            17,
+            row,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            row,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            row,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+        ctx.print(21, row, item);
+    }
+}
+
+// 交易界面，买的时候列出商人的货物，卖的时候列出自己的背包
+pub fn show_vendor(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    vendor: Entity,
+    mode: VendorMode,
+) -> (VendorResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let quantities = gs.ecs.read_storage::<Quantity>();
+    let values = gs.ecs.read_storage::<ItemValue>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let entities = gs.ecs.entities();
+    let gold = gs
+        .ecs
+        .read_storage::<Wallet>()
+        .get(*player_entity)
+        .map_or(0, |w| w.gold);
+
+    let owner = match mode {
+        VendorMode::Buy => vendor,
+        VendorMode::Sell => *player_entity,
+    };
+    let mut stock: Vec<Entity> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    for (entity, _pack, name, value) in (&entities, &backpack, &names, &values)
+        .join()
+        .filter(|item| item.1.owner == owner)
     {
-        // 选中和没有选中是不同的样式
-        if selection == MainMenuSelection::NewGame {
-            ctx.print_color_centered(
-                y,
-                RGB::named(rltk::MAGENTA),
-                RGB::named(rltk::BLACK),
-                "Begin New Game",
-            );
-        } else {
-            ctx.print_color_centered(
-                y,
-                RGB::named(rltk::WHITE),
-                RGB::named(rltk::BLACK),
-                "Begin New Game",
-            );
-        }
-        //
-        if save_exists {
-            if selection == MainMenuSelection::LoadGame {
-                ctx.print_color_centered(
-                    y + 1,
-                    RGB::named(rltk::MAGENTA),
-                    RGB::named(rltk::BLACK),
-                    "Load Game",
-                );
+        let price = match mode {
+            VendorMode::Buy => value.value,
+            VendorMode::Sell => sell_price(value.value),
+        };
+        let item_name = with_quantity(
+            item_display_name(name, obfuscated.get(entity), &registry),
+            quantities.get(entity),
+        );
+        labels.push(format!("{:<30} {:>4} gold", item_name, price));
+        stock.push(entity);
+    }
+
+    let (title, footer) = match mode {
+        VendorMode::Buy => (
+            format!("Buy Which Item? (You have {} gold)", gold),
+            "TAB to sell, ESCAPE to leave",
+        ),
+        VendorMode::Sell => (
+            format!("Sell Which Item? (You have {} gold)", gold),
+            "TAB to buy, ESCAPE to leave",
+        ),
+    };
+    draw_item_menu(ctx, &title, footer, 51, &labels);
+
+    match ctx.key {
+        None => (VendorResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (VendorResult::Cancel, None),
+            VirtualKeyCode::Tab => (VendorResult::SwitchMode, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < stock.len() as i32 {
+                    return (VendorResult::Selected, Some(stock[selection as usize]));
+                }
+                (VendorResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+// ------------------------------------ crafting ----------------------------
+// 合成菜单，列出所有的配方，材料不够的配方 标注出来
+pub fn crafting_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<usize>) {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let labels: Vec<String> = RECIPES
+        .iter()
+        .map(|recipe| {
+            if can_craft(&gs.ecs, player_entity, recipe) {
+                recipe.describe()
             } else {
-                ctx.print_color_centered(
-                    y + 1,
-                    RGB::named(rltk::WHITE),
+                format!("{} (missing)", recipe.describe())
+            }
+        })
+        .collect();
+    draw_item_menu(ctx, "Craft Which Item?", "ESCAPE to cancel", 51, &labels);
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < RECIPES.len() as i32 {
+                    return (ItemMenuResult::Selected, Some(selection as usize));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+// 法术菜单，列出学会的法术 和 需要的法力
+pub fn show_spells(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let known_spells = gs.ecs.read_storage::<KnownSpell>();
+    let spells = gs.ecs.read_storage::<Spell>();
+    let entities = gs.ecs.entities();
+    let mana = gs
+        .ecs
+        .read_storage::<Mana>()
+        .get(*player_entity)
+        .map_or(0, |m| m.current);
+
+    let mut known: Vec<Entity> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    for (entity, _known, name, spell) in (&entities, &known_spells, &names, &spells)
+        .join()
+        .filter(|(_, k, _, _)| k.owner == *player_entity)
+    {
+        known.push(entity);
+        if spell.mana_cost > mana {
+            labels.push(format!(
+                "{} ({} mp, not enough mana)",
+                name.name, spell.mana_cost
+            ));
+        } else {
+            labels.push(format!("{} ({} mp)", name.name, spell.mana_cost));
+        }
+    }
+    draw_item_menu(ctx, "Cast Which Spell?", "ESCAPE to cancel", 45, &labels);
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < known.len() as i32 {
+                    return (ItemMenuResult::Selected, Some(known[selection as usize]));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+// 选择要扔出去的物品
+pub fn throw_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let quantities = gs.ecs.read_storage::<Quantity>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let entities = gs.ecs.entities();
+
+    let mut items: Vec<Entity> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+    for (entity, _pack, name) in (&entities, &backpack, &names)
+        .join()
+        .filter(|item| item.1.owner == *player_entity)
+    {
+        items.push(entity);
+        labels.push(with_quantity(
+            item_display_name(name, obfuscated.get(entity), &registry),
+            quantities.get(entity),
+        ));
+    }
+    draw_item_menu(ctx, "Throw Which Item?", "ESCAPE to cancel", 31, &labels);
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < items.len() as i32 {
+                    return (ItemMenuResult::Selected, Some(items[selection as usize]));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+// 装备栏中槽的显示顺序 和 名字
+const EQUIPMENT_SLOTS: [(EquipmentSlot, &str); 10] = [
+    (EquipmentSlot::Head, "Head"),
+    (EquipmentSlot::Amulet, "Amulet"),
+    (EquipmentSlot::Torso, "Torso"),
+    (EquipmentSlot::Hands, "Hands"),
+    (EquipmentSlot::Ring, "Ring"),
+    (EquipmentSlot::Melee, "Main Hand"),
+    (EquipmentSlot::Shield, "Off Hand"),
+    (EquipmentSlot::Ranged, "Ranged"),
+    (EquipmentSlot::Legs, "Legs"),
+    (EquipmentSlot::Feet, "Feet"),
+];
+
+// 装备栏，列出每一个槽 和 槽中的装备 以及装备的加成
+pub fn show_equipment(gs: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+    let registry = gs.ecs.fetch::<ItemRegistry>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let power_bonuses = gs.ecs.read_storage::<MeleePowerBonus>();
+    let defense_bonuses = gs.ecs.read_storage::<DefenseBonus>();
+    let two_handed = gs.ecs.read_storage::<TwoHanded>();
+    let elemental_damages = gs.ecs.read_storage::<ElementalDamage>();
+    let attribute_bonuses = gs.ecs.read_storage::<AttributeBonus>();
+    let life_steals = gs.ecs.read_storage::<LifeSteal>();
+    let confuse_on_hits = gs.ecs.read_storage::<ConfuseOnHit>();
+    let ranged_weapons = gs.ecs.read_storage::<RangedWeapon>();
+    let entities = gs.ecs.entities();
+
+    let count = EQUIPMENT_SLOTS.len() as i32;
+    let mut y = 25 - count / 2;
+    ctx.draw_box(
+        5,
+        y - 2,
+        70,
+        count + 5,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        8,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Equipment",
+    );
+    ctx.print_color(
+        8,
+        y + count + 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to close",
+    );
+
+    let mut total_power = 0;
+    let mut total_defense = 0;
+    for (slot, label) in EQUIPMENT_SLOTS.iter() {
+        ctx.print_color(
+            7,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            label,
+        );
+
+        let item = (&entities, &equipped)
+            .join()
+            .find(|(_, e)| e.owner == *player_entity && e.slot == *slot)
+            .map(|(entity, _)| entity);
+        // 双手武器 同时占用了副手
+        let held_two_handed = (&entities, &equipped, &two_handed)
+            .join()
+            .any(|(_, e, _)| e.owner == *player_entity && e.slot == EquipmentSlot::Melee);
+
+        match item {
+            Some(item) => {
+                let name =
+                    item_display_name(names.get(item).unwrap(), obfuscated.get(item), &registry);
+                let name_len = name.len() as i32;
+                ctx.print(19, y, name);
+
+                let mut bonuses: Vec<String> = Vec::new();
+                if let Some(weapon) = ranged_weapons.get(item) {
+                    bonuses.push(format!("{:+} pow, range {}", weapon.power, weapon.range));
+                }
+                if let Some(power) = power_bonuses.get(item) {
+                    total_power += power.power;
+                    bonuses.push(format!("{:+} pow", power.power));
+                }
+                if let Some(defense) = defense_bonuses.get(item) {
+                    total_defense += defense.defense;
+                    bonuses.push(format!("{:+} def", defense.defense));
+                }
+                if let Some(elemental) = elemental_damages.get(item) {
+                    bonuses.push(format!(
+                        "{:+} {}",
+                        elemental.damage,
+                        damage_type_name(elemental.damage_type)
+                    ));
+                }
+                if let Some(attribute) = attribute_bonuses.get(item) {
+                    bonuses.push(format!("{:+} str", attribute.strength));
+                }
+                if life_steals.get(item).is_some() {
+                    bonuses.push("drain".to_string());
+                }
+                if confuse_on_hits.get(item).is_some() {
+                    bonuses.push("confuse".to_string());
+                }
+                if !bonuses.is_empty() {
+                    ctx.print_color(
+                        20 + name_len,
+                        y,
+                        RGB::named(rltk::GREEN),
+                        RGB::named(rltk::BLACK),
+                        format!("({})", bonuses.join(", ")),
+                    );
+                }
+            }
+            None if *slot == EquipmentSlot::Shield && held_two_handed => {
+                ctx.print_color(
+                    19,
+                    y,
+                    RGB::named(rltk::GRAY),
                     RGB::named(rltk::BLACK),
-                    "Load Game",
+                    "(two-handed)",
                 );
             }
+            None => {
+                ctx.print_color(19, y, RGB::named(rltk::GRAY), RGB::named(rltk::BLACK), "-");
+            }
+        }
+        y += 1;
+    }
+
+    ctx.print_color(
+        7,
+        y + 1,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!(
+            "Total: {:+} power, {:+} defense",
+            total_power, total_defense
+        ),
+    );
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
+// 角色面板，显示基础属性、装备的加成、饥饿、身上的状态、回合数 和 杀敌数
+pub fn show_character(gs: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
+    let ecs = &gs.ecs;
+    let player_entity = *ecs.fetch::<Entity>();
+    let white = RGB::named(rltk::WHITE);
+    let mut lines: Vec<(RGB, String)> = Vec::new();
+
+    if let Some(stats) = ecs.read_storage::<CombatStats>().get(player_entity) {
+        lines.push((
+            white,
+            format!("HP:         {} / {}", stats.hp, stats.max_hp),
+        ));
+        if let Some(mana) = ecs.read_storage::<Mana>().get(player_entity) {
+            lines.push((
+                white,
+                format!("MP:         {} / {}", mana.current, mana.max),
+            ));
+        }
+
+        // 身上所有装备的加成
+        let equipped = ecs.read_storage::<Equipped>();
+        let power_bonus: i32 = (&equipped, &ecs.read_storage::<MeleePowerBonus>())
+            .join()
+            .filter(|(e, _)| e.owner == player_entity)
+            .map(|(_, b)| b.power)
+            .sum();
+        let defense_bonus: i32 = (&equipped, &ecs.read_storage::<DefenseBonus>())
+            .join()
+            .filter(|(e, _)| e.owner == player_entity)
+            .map(|(_, b)| b.defense)
+            .sum();
+        lines.push((
+            white,
+            format!(
+                "Power:      {} ({:+} equipment) = {}",
+                stats.power,
+                power_bonus,
+                stats.power + power_bonus
+            ),
+        ));
+        lines.push((
+            white,
+            format!(
+                "Defense:    {} ({:+} equipment) = {}",
+                stats.defense,
+                defense_bonus,
+                stats.defense + defense_bonus
+            ),
+        ));
+    }
+    if let Some(attributes) = ecs.read_storage::<Attributes>().get(player_entity) {
+        lines.push((white, format!("Strength:   {}", attributes.strength)));
+        lines.push((white, format!("Perception: {}", attributes.perception)));
+    }
+    if let Some(burden) = ecs.read_storage::<Burden>().get(player_entity) {
+        lines.push((
+            white,
+            format!(
+                "Carrying:   {:.1}/{:.0} lbs",
+                burden.carried, burden.capacity
+            ),
+        ));
+    }
+    lines.push((white, String::new()));
+
+    if let Some(clock) = ecs.read_storage::<HungerClock>().get(player_entity) {
+        let (color, state) = match clock.state {
+            HungerState::WellFed => (RGB::named(rltk::GREEN), "Well Fed"),
+            HungerState::Normal => (white, "Normal"),
+            HungerState::Hungry => (RGB::named(rltk::ORANGE), "Hungry"),
+            HungerState::Starving => (RGB::named(rltk::RED), "Starving"),
+        };
+        lines.push((color, format!("Hunger:     {}", state)));
+    }
+
+    // 身上的状态 和 剩余的回合
+    let mut effects: Vec<String> = Vec::new();
+    if ecs.read_storage::<Asleep>().get(player_entity).is_some() {
+        effects.push("asleep".to_string());
+    }
+    if let Some(confusion) = ecs.read_storage::<Confusion>().get(player_entity) {
+        effects.push(format!("confused ({} turns)", confusion.turns));
+    }
+    if let Some(poison) = ecs.read_storage::<Poisoned>().get(player_entity) {
+        effects.push(format!("poisoned ({} turns)", poison.turns));
+    }
+    if ecs
+        .read_storage::<Burden>()
+        .get(player_entity)
+        .is_some_and(|b| b.is_encumbered())
+    {
+        effects.push("overloaded".to_string());
+    }
+    if effects.is_empty() {
+        lines.push((white, "Effects:    none".to_string()));
+    } else {
+        let text = format!("Effects:    {}", effects.join(", "));
+        let cyan = RGB::named(rltk::CYAN);
+        lines.extend(wrap_text(&text, 56).into_iter().map(|l| (cyan, l)));
+    }
+    lines.push((white, String::new()));
+
+    let depth = ecs.fetch::<Map>().depth;
+    let turn = ecs.fetch::<GameLog>().turn;
+    let kills = ecs
+        .read_storage::<KillCount>()
+        .get(player_entity)
+        .map_or(0, |k| k.kills);
+    let gold = ecs
+        .read_storage::<Wallet>()
+        .get(player_entity)
+        .map_or(0, |w| w.gold);
+    lines.push((white, format!("Depth:      {}", depth)));
+    lines.push((white, format!("Turn:       {}", turn)));
+    lines.push((white, format!("Kills:      {}", kills)));
+    lines.push((RGB::named(rltk::GOLD), format!("Gold:       {}", gold)));
+
+    let count = lines.len() as i32;
+    let y = 25 - count / 2;
+    ctx.draw_box(
+        10,
+        y - 2,
+        60,
+        count + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        13,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Character",
+    );
+    ctx.print_color(
+        13,
+        y + count + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to close",
+    );
+    for (i, (color, line)) in lines.iter().enumerate() {
+        ctx.print_color(12, y + i as i32, *color, RGB::named(rltk::BLACK), line);
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
+// 绘制攻击菜单
+// 返回 菜单 的 状态，和 选项 所在位置
+pub fn ranged_target(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    range: i32,
+    radius: Option<i32>,
+) -> (ItemMenuResult, Option<Point>) {
+    // start by obtaining the player's location and viewshed, and it
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let player_pos = gs.ecs.fetch::<Point>();
+    let viewsheds = gs.ecs.read_storage::<Viewshed>();
+
+    // 选中 选项 所在的颜色
+    ctx.print_color(
+        5,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Select Target:",
+    );
+
+    // Highlight available target cells
+    let mut available_cells = Vec::new();
+    let visible = viewsheds.get(*player_entity);
+    if let Some(visible) = visible {
+        // we have a viewshed 遍历视域范围内的物体,将视域中cell 设置为蓝色
+        for idx in visible.visible_tiles.iter() {
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
+            if distance <= range as f32 {
+                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
+                available_cells.push(idx);
+            }
+        }
+    } else {
+        return (ItemMenuResult::Cancel, None);
+    }
+
+    // Draw mouse cursor
+    let mouse_pos = ctx.mouse_pos();
+
+    let mut valid_target = false;
+    // 如果 鼠标指向有效目标，高亮为青色
+    for idx in available_cells.iter() {
+        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
+            valid_target = true;
+        }
+    }
+
+    // 对于有效的目标，设置背景
+    if valid_target {
+        // 显示投射物的飞行路线，落点 和 爆炸的范围
+        let path = projectile_path(&gs.ecs, *player_pos, Point::new(mouse_pos.0, mouse_pos.1));
+        if let Some(impact) = path.last() {
+            if let Some(radius) = radius {
+                let map = gs.ecs.fetch::<Map>();
+                for tile in rltk::field_of_view(*impact, radius, &*map).iter() {
+                    ctx.set_bg(tile.x, tile.y, RGB::named(rltk::ORANGE));
+                }
+            }
+            for pt in path.iter() {
+                ctx.set_bg(pt.x, pt.y, RGB::named(rltk::YELLOW));
+            }
+            ctx.set_bg(impact.x, impact.y, RGB::named(rltk::RED));
+        }
+        ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
+        if ctx.left_click {
+            // If you click a valid cell, it returns targeting information for where you are aiming - otherwise, it cancels
+            return (
+                ItemMenuResult::Selected,
+                Some(Point::new(mouse_pos.0, mouse_pos.1)),
+            );
+        }
+    } else {
+        ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
+        if ctx.left_click {
+            return (ItemMenuResult::Cancel, None);
+        }
+    }
+
+    (ItemMenuResult::NoResponse, None)
+}
+
+// 射击的选项
+#[derive(PartialEq, Copy, Clone)]
+pub enum FireResult {
+    NoResponse,
+    Cancel,
+    // 切换到下一个目标
+    NextTarget,
+    Fire,
+}
+
+// 用远程武器瞄准 target，显示飞行路线
+pub fn fire_target(gs: &mut State, ctx: &mut Rltk, target: Entity) -> FireResult {
+    let player_pos = gs.ecs.fetch::<Point>();
+    let positions = gs.ecs.read_storage::<Position>();
+    let names = gs.ecs.read_storage::<Name>();
+
+    let target_pos = match positions.get(target) {
+        Some(pos) => Point::new(pos.x, pos.y),
+        None => return FireResult::Cancel,
+    };
+    let name = names.get(target).map_or("", |n| n.name.as_str());
+    ctx.print_color(
+        5,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!(
+            "Fire at {}: TAB next target, F/ENTER fire, ESC cancel",
+            name
+        ),
+    );
+
+    let path = projectile_path(&gs.ecs, *player_pos, target_pos);
+    for pt in path.iter() {
+        ctx.set_bg(pt.x, pt.y, RGB::named(rltk::YELLOW));
+    }
+    if let Some(impact) = path.last() {
+        ctx.set_bg(impact.x, impact.y, RGB::named(rltk::RED));
+    }
+    ctx.set_bg(target_pos.x, target_pos.y, RGB::named(rltk::CYAN));
+
+    match ctx.key {
+        None => FireResult::NoResponse,
+        Some(key) => match key {
+            VirtualKeyCode::Escape => FireResult::Cancel,
+            VirtualKeyCode::Tab => FireResult::NextTarget,
+            VirtualKeyCode::F | VirtualKeyCode::Return => FireResult::Fire,
+            _ => FireResult::NoResponse,
+        },
+    }
+}
+
+// --------------------------------- look mode-------------------------------
+// 观察模式的选项
+#[derive(PartialEq, Copy, Clone)]
+pub enum LookResult {
+    NoResponse,
+    Cancel,
+    // 光标移动到了新的位置
+    Moved,
+}
+
+// 观察模式的描述框 每行的宽度
+const LOOK_WIDTH: usize = 34;
+// 没有鉴定的物品 的描述
+const UNKNOWN_POTION: &str =
+    "An unlabelled potion. You won't know what it does until you drink it or identify it.";
+const UNKNOWN_SCROLL: &str = "A scroll covered in unfamiliar writing. You won't know what it does until you read it or identify it.";
+
+// 用方向键移动光标，TAB 在视野中的怪物和物品之间切换，显示光标处的详细描述
+pub fn look_mode(gs: &mut State, ctx: &mut Rltk, cursor: Point) -> (LookResult, Point) {
+    ctx.print_color(
+        5,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Look: direction keys to move, TAB next target, ESC to exit",
+    );
+    ctx.set_bg(cursor.x, cursor.y, RGB::named(rltk::MAGENTA));
+
+    // 描述框 画在光标的另一边，不挡住光标
+    let lines = describe_tile(&gs.ecs, cursor);
+    let x = if cursor.x < 40 { 42 } else { 1 };
+    ctx.draw_box(
+        x,
+        2,
+        LOOK_WIDTH as i32 + 3,
+        lines.len() as i32 + 1,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    for (i, (color, line)) in lines.iter().enumerate() {
+        ctx.print_color(x + 2, 3 + i as i32, *color, RGB::named(rltk::BLACK), line);
+    }
+
+    let (width, height) = {
+        let map = gs.ecs.fetch::<Map>();
+        (map.width, map.height)
+    };
+    let step = |dx: i32, dy: i32| {
+        let moved = Point::new(
+            (cursor.x + dx).clamp(0, width - 1),
+            (cursor.y + dy).clamp(0, height - 1),
+        );
+        (LookResult::Moved, moved)
+    };
+    match ctx.key {
+        None => (LookResult::NoResponse, cursor),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (LookResult::Cancel, cursor),
+            VirtualKeyCode::Tab => (LookResult::Moved, next_look_target(&gs.ecs, cursor)),
+            // 光标 和玩家一样 用移动键移动
+            _ => match gs
+                .ecs
+                .fetch::<KeyMap>()
+                .action(key)
+                .and_then(|a| a.direction())
+            {
+                Some((dx, dy)) => step(dx, dy),
+                None => (LookResult::NoResponse, cursor),
+            },
+        },
+    }
+}
+
+// 视野中 下一个怪物 或者 物品 的位置，按离玩家的距离排列
+fn next_look_target(ecs: &World, cursor: Point) -> Point {
+    let map = ecs.fetch::<Map>();
+    let player_pos = ecs.fetch::<Point>();
+    let positions = ecs.read_storage::<Position>();
+    let monsters = ecs.read_storage::<Monster>();
+    let items = ecs.read_storage::<Item>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let entities = ecs.entities();
+
+    let mut targets: Vec<(f32, Point)> = Vec::new();
+    for (entity, pos, _hidden) in (&entities, &positions, !&hidden).join() {
+        let point = Point::new(pos.x, pos.y);
+        let idx = map.xy_idx(pos.x, pos.y);
+        let interesting = monsters.get(entity).is_some() || items.get(entity).is_some();
+        if interesting && map.visible_tiles[idx] && !targets.iter().any(|(_, p)| *p == point) {
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, point);
+            targets.push((distance, point));
+        }
+    }
+    targets.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    match targets.iter().position(|(_, p)| *p == cursor) {
+        Some(i) => targets[(i + 1) % targets.len()].1,
+        None => targets.first().map_or(cursor, |(_, p)| *p),
+    }
+}
+
+// 光标处的 tile 和 上面所有可见的实体 的描述
+fn describe_tile(ecs: &World, cursor: Point) -> Vec<(RGB, String)> {
+    let map = ecs.fetch::<Map>();
+    let names = ecs.read_storage::<Name>();
+    let descriptions = ecs.read_storage::<Description>();
+    let obfuscated = ecs.read_storage::<ObfuscatedName>();
+    let registry = ecs.fetch::<ItemRegistry>();
+    let quantities = ecs.read_storage::<Quantity>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let equippable = ecs.read_storage::<Equippable>();
+    let potions = ecs.read_storage::<Potion>();
+    let entities = ecs.entities();
+
+    let mut lines: Vec<(RGB, String)> = Vec::new();
+    let white = RGB::named(rltk::WHITE);
+    let grey = RGB::named(rltk::GREY);
+    if cursor.x < 0 || cursor.x >= map.width || cursor.y < 0 || cursor.y >= map.height {
+        return lines;
+    }
+    let idx = map.xy_idx(cursor.x, cursor.y);
+    if !map.revealed_tiles[idx] {
+        lines.push((grey, "You haven't seen this place.".to_string()));
+        return lines;
+    }
+    if !map.visible_tiles[idx] {
+        let text = format!("You remember {} here.", tile_description(map.tiles[idx]));
+        lines.extend(wrap_text(&text, LOOK_WIDTH).into_iter().map(|l| (grey, l)));
+        return lines;
+    }
+    let text = format!("You see {}.", tile_description(map.tiles[idx]));
+    lines.extend(wrap_text(&text, LOOK_WIDTH).into_iter().map(|l| (white, l)));
+
+    for (entity, name, pos, _hidden) in (&entities, &names, &positions, !&hidden).join() {
+        if pos.x != cursor.x || pos.y != cursor.y {
+            continue;
+        }
+        lines.push((white, String::new()));
+        let display_name = item_display_name(name, obfuscated.get(entity), &registry);
+        lines.push((
+            RGB::named(rltk::YELLOW),
+            with_quantity(display_name, quantities.get(entity)),
+        ));
+
+        // 生物的 生命值 装备 和 状态
+        if let Some(stats) = combat_stats.get(entity) {
+            lines.push((
+                RGB::named(rltk::ORANGE),
+                format!("Health: {}", health_state(stats)),
+            ));
+
+            let worn: Vec<String> = (&equipped, &names)
+                .join()
+                .filter(|(e, _)| e.owner == entity)
+                .map(|(_, n)| n.name.clone())
+                .collect();
+            if !worn.is_empty() {
+                let text = format!("Equipment: {}", worn.join(", "));
+                lines.extend(wrap_text(&text, LOOK_WIDTH).into_iter().map(|l| (white, l)));
+            }
+
+            let status = status_effects(ecs, entity);
+            if !status.is_empty() {
+                let text = format!("Status: {}", status.join(", "));
+                let cyan = RGB::named(rltk::CYAN);
+                lines.extend(wrap_text(&text, LOOK_WIDTH).into_iter().map(|l| (cyan, l)));
+            }
         }
-        if selection == MainMenuSelection::Quit {
-            ctx.print_color_centered(
-                y,
-                RGB::named(rltk::MAGENTA),
-                RGB::named(rltk::BLACK),
-                "Quit",
-            );
+
+        // 没有鉴定的卷轴和药水 不能透露它的作用，被诅咒的装备 看起来和普通的一样
+        let unidentified = obfuscated.get(entity).is_some() && !registry.is_identified(&name.name);
+        let text = if unidentified && equippable.get(entity).is_none() {
+            if potions.get(entity).is_some() {
+                Some(UNKNOWN_POTION.to_string())
+            } else {
+                Some(UNKNOWN_SCROLL.to_string())
+            }
         } else {
-            ctx.print_color_centered(y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
+            descriptions.get(entity).map(|d| d.text.clone())
+        };
+        if let Some(text) = text {
+            lines.extend(wrap_text(&text, LOOK_WIDTH).into_iter().map(|l| (grey, l)));
+        }
+    }
+    // 一格上的东西太多时 只显示放得下的部分
+    lines.truncate(38);
+    lines
+}
+
+// 生命值的大致状态
+fn health_state(stats: &CombatStats) -> &'static str {
+    let percent = stats.hp * 100 / i32::max(1, stats.max_hp);
+    if percent >= 100 {
+        "unhurt"
+    } else if percent >= 75 {
+        "lightly wounded"
+    } else if percent >= 40 {
+        "wounded"
+    } else if percent >= 15 {
+        "badly wounded"
+    } else {
+        "near death"
+    }
+}
+
+// 实体身上的状态
+fn status_effects(ecs: &World, entity: Entity) -> Vec<&'static str> {
+    let mut status = Vec::new();
+    if ecs.read_storage::<Asleep>().get(entity).is_some() {
+        status.push("asleep");
+    }
+    if ecs.read_storage::<Confusion>().get(entity).is_some() {
+        status.push("confused");
+    }
+    if ecs.read_storage::<Poisoned>().get(entity).is_some() {
+        status.push("poisoned");
+    }
+    if let Some(clock) = ecs.read_storage::<HungerClock>().get(entity) {
+        match clock.state {
+            HungerState::WellFed => status.push("well fed"),
+            HungerState::Normal => {}
+            HungerState::Hungry => status.push("hungry"),
+            HungerState::Starving => status.push("starving"),
+        }
+    }
+    if ecs
+        .read_storage::<Burden>()
+        .get(entity)
+        .is_some_and(|b| b.is_encumbered())
+    {
+        status.push("overloaded");
+    }
+    status
+}
+
+// 按单词折行
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// --------------------------------- message log-------------------------------
+// 日志查看器的选项
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogViewResult {
+    NoResponse,
+    Cancel,
+    // 翻到了新的位置
+    Scrolled,
+}
+
+// 一屏显示的日志条数
+const LOG_PAGE: i32 = 45;
+
+// 全屏的日志，offset 是从最新的日志 往前翻过的条数
+pub fn show_log(gs: &mut State, ctx: &mut Rltk, offset: i32) -> (LogViewResult, i32) {
+    let log = gs.ecs.fetch::<GameLog>();
+    let entries: Vec<&LogEntry> = log.shown(gs.ecs.fetch::<Settings>().brief()).collect();
+    let total = entries.len() as i32;
+    let max_offset = i32::max(0, total - LOG_PAGE);
+    let offset = offset.clamp(0, max_offset);
+
+    ctx.cls();
+    ctx.draw_box(
+        0,
+        0,
+        79,
+        49,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        3,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Message Log",
+    );
+    ctx.print_color(
+        3,
+        49,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "UP/DOWN, PAGE UP/PAGE DOWN to scroll, ESCAPE to close",
+    );
+
+    // 最新的日志 在最下面
+    let end = (total - offset) as usize;
+    let start = end.saturating_sub(LOG_PAGE as usize);
+    for (i, entry) in entries[start..end].iter().enumerate() {
+        let y = 2 + i as i32;
+        ctx.print_color(
+            2,
+            y,
+            RGB::named(rltk::GREY),
+            RGB::named(rltk::BLACK),
+            format!("{:>5}", entry.turn),
+        );
+        print_log_entry(ctx, 9, y, entry);
+    }
+
+    let scroll = |delta: i32| {
+        (
+            LogViewResult::Scrolled,
+            (offset + delta).clamp(0, max_offset),
+        )
+    };
+    match ctx.key {
+        None => (LogViewResult::NoResponse, offset),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (LogViewResult::Cancel, offset),
+            VirtualKeyCode::Up | VirtualKeyCode::K | VirtualKeyCode::Numpad8 => scroll(1),
+            VirtualKeyCode::Down | VirtualKeyCode::J | VirtualKeyCode::Numpad2 => scroll(-1),
+            VirtualKeyCode::PageUp => scroll(LOG_PAGE),
+            VirtualKeyCode::PageDown => scroll(-LOG_PAGE),
+            _ => (LogViewResult::NoResponse, offset),
+        },
+    }
+}
+
+// --------------------------------- main menu-------------------------------
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MainMenuSelection {
+    NewGame,
+    LoadGame,
+    Options,
+    Quit,
+}
+#[derive(PartialEq, Copy, Clone)]
+pub enum MainMenuResult {
+    NoSelection { selected: MainMenuSelection },
+    Selected { selected: MainMenuSelection },
+}
+
+// 绘制主菜单
+pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
+    // 检查 游戏状态保存文件是否存在
+    let save_exists = super::saveload_system::does_save_exist();
+    let runstate = gs.ecs.fetch::<RunState>();
+
+    // 渲染主菜单背景图片
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+
+    // 从 State 得到现在所处的运行状态，如果当前的状态是 MainMenu, 显示主菜单
+    // add a box for the menu and text.
+    ctx.draw_box_double(
+        24,
+        18,
+        31,
+        10,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        20,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Rust Roguelike Tutorial",
+    );
+    ctx.print_color_centered(
+        21,
+        RGB::named(rltk::CYAN),
+        RGB::named(rltk::BLACK),
+        "by Herbert Wolverson",
+    );
+    ctx.print_color_centered(
+        22,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "Use Up/Down Arrows and Enter",
+    );
+
+    // 固定菜单选项的间距
+    let mut y = 24;
+    //  selection 选中哪个选项
+    if let RunState::MainMenu {
+        menu_selection: selection,
+    } = *runstate
+    {
+        // 没有存档时 不显示 Load Game
+        let entries: Vec<(MainMenuSelection, &str)> = [
+            (MainMenuSelection::NewGame, "Begin New Game"),
+            (MainMenuSelection::LoadGame, "Load Game"),
+            (MainMenuSelection::Options, "Options"),
+            (MainMenuSelection::Quit, "Quit"),
+        ]
+        .iter()
+        .copied()
+        .filter(|(entry, _)| save_exists || *entry != MainMenuSelection::LoadGame)
+        .collect();
+
+        // 选中和没有选中是不同的样式
+        for (entry, label) in entries.iter() {
+            let color = if *entry == selection {
+                RGB::named(rltk::MAGENTA)
+            } else {
+                RGB::named(rltk::WHITE)
+            };
+            ctx.print_color_centered(y, color, RGB::named(rltk::BLACK), label);
+            y += 1;
         }
 
         // 按键
+        let current = entries
+            .iter()
+            .position(|(entry, _)| *entry == selection)
+            .unwrap_or(0);
+        let count = entries.len();
         match ctx.key {
             // 匹配 按键
             // 如果按键没有按下，就是当前选中的选项
@@ -696,25 +1858,13 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     }
                 }
                 VirtualKeyCode::Up => {
-                    let newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame,
-                    }
                     return MainMenuResult::NoSelection {
-                        selected: newselection,
+                        selected: entries[(current + count - 1) % count].0,
                     };
                 }
                 VirtualKeyCode::Down => {
-                    let newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
-                    }
                     return MainMenuResult::NoSelection {
-                        selected: newselection,
+                        selected: entries[(current + 1) % count].0,
                     };
                 }
                 VirtualKeyCode::Return => {
@@ -735,6 +1885,132 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     }
 }
 
+// 选项界面中的设置项
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum OptionsSelection {
+    MapgenVisualizer,
+    Scanlines,
+    Font,
+    Size,
+    Verbosity,
+    Back,
+}
+
+const OPTIONS: [OptionsSelection; 6] = [
+    OptionsSelection::MapgenVisualizer,
+    OptionsSelection::Scanlines,
+    OptionsSelection::Font,
+    OptionsSelection::Size,
+    OptionsSelection::Verbosity,
+    OptionsSelection::Back,
+];
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum OptionsMenuResult {
+    NoSelection { selected: OptionsSelection },
+    Changed { selected: OptionsSelection },
+    Back,
+}
+
+// 绘制选项界面，Enter 或者 左右键 修改选中的设置
+pub fn options_menu(gs: &mut State, ctx: &mut Rltk) -> OptionsMenuResult {
+    let settings = gs.ecs.fetch::<Settings>();
+    let runstate = gs.ecs.fetch::<RunState>();
+
+    let assets = gs.ecs.fetch::<RexAssets>();
+    ctx.render_xp_sprite(&assets.menu, 0, 0);
+
+    ctx.draw_box_double(
+        20,
+        16,
+        39,
+        14,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        18,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Options",
+    );
+    ctx.print_color_centered(
+        19,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "Up/Down to select, Enter to change",
+    );
+    ctx.print_color_centered(
+        28,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "* requires restart",
+    );
+
+    let selection = match *runstate {
+        RunState::OptionsMenu { selection } => selection,
+        _ => OptionsSelection::MapgenVisualizer,
+    };
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    for (i, option) in OPTIONS.iter().enumerate() {
+        let (label, value) = match option {
+            OptionsSelection::MapgenVisualizer => (
+                "Map generation",
+                on_off(settings.show_mapgen_visualizer).to_string(),
+            ),
+            OptionsSelection::Scanlines => ("Scanlines", on_off(settings.scanlines).to_string()),
+            // 字体 和 大小 在创建窗口时决定，重新启动后 才会生效
+            OptionsSelection::Font => ("Font *", settings.font.name().to_string()),
+            OptionsSelection::Size => ("Size *", format!("{}x", settings.scale)),
+            OptionsSelection::Verbosity => (
+                "Messages",
+                match settings.verbosity {
+                    Verbosity::All => "All".to_string(),
+                    Verbosity::Brief => "Brief".to_string(),
+                },
+            ),
+            OptionsSelection::Back => ("Back", String::new()),
+        };
+        let color = if *option == selection {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+        let y = 21 + i as i32;
+        ctx.print_color(24, y, color, RGB::named(rltk::BLACK), label);
+        ctx.print_color(42, y, color, RGB::named(rltk::BLACK), value);
+    }
+
+    let current = OPTIONS.iter().position(|o| *o == selection).unwrap_or(0);
+    let count = OPTIONS.len();
+    match ctx.key {
+        None => OptionsMenuResult::NoSelection {
+            selected: selection,
+        },
+        Some(key) => match key {
+            VirtualKeyCode::Escape => OptionsMenuResult::Back,
+            VirtualKeyCode::Up => OptionsMenuResult::NoSelection {
+                selected: OPTIONS[(current + count - 1) % count],
+            },
+            VirtualKeyCode::Down => OptionsMenuResult::NoSelection {
+                selected: OPTIONS[(current + 1) % count],
+            },
+            VirtualKeyCode::Return | VirtualKeyCode::Left | VirtualKeyCode::Right => {
+                if selection == OptionsSelection::Back {
+                    OptionsMenuResult::Back
+                } else {
+                    OptionsMenuResult::Changed {
+                        selected: selection,
+                    }
+                }
+            }
+            _ => OptionsMenuResult::NoSelection {
+                selected: selection,
+            },
+        },
+    }
+}
+
 // 游戏结束的界面
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult {
@@ -775,3 +2051,59 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
         Some(_) => GameOverResult::QuitToMenu,
     }
 }
+
+// 帮助界面，列出每个动作 当前绑定的按键
+pub fn show_help(gs: &mut State, ctx: &mut Rltk) -> ItemMenuResult {
+    let keymap = gs.ecs.fetch::<KeyMap>();
+
+    let count = Action::ALL.len() as i32;
+    let y = 25 - count / 2;
+    ctx.draw_box(
+        10,
+        y - 2,
+        60,
+        count + 4,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        13,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Key Bindings",
+    );
+    ctx.print_color(
+        13,
+        y + count + 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!("Edit {} to rebind, ESCAPE to close", KEYMAP_FILE),
+    );
+
+    for (i, action) in Action::ALL.iter().enumerate() {
+        let y = y + i as i32;
+        ctx.print(12, y, action.description());
+        let keys: Vec<String> = keymap.keys(*action).iter().map(|k| key_name(*k)).collect();
+        let (color, text) = if keys.is_empty() {
+            (RGB::named(rltk::GRAY), "-".to_string())
+        } else {
+            (RGB::named(rltk::CYAN), keys.join(", "))
+        };
+        ctx.print_color(36, y, color, RGB::named(rltk::BLACK), text);
+    }
+    // 跑步 不是单独的按键
+    ctx.print(12, y + count, "Run");
+    ctx.print_color(
+        36,
+        y + count,
+        RGB::named(rltk::CYAN),
+        RGB::named(rltk::BLACK),
+        "SHIFT + move",
+    );
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
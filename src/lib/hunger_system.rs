@@ -1,4 +1,6 @@
-use super::{gamelog::GameLog, HungerClock, HungerState, RunState, SufferDamage};
+use super::{
+    gamelog::GameLog, EffectQueue, EffectType, HungerClock, HungerState, RunState, Targets,
+};
 use specs::prelude::*;
 
 pub struct HungerSystem {}
@@ -9,12 +11,11 @@ impl<'a> System<'a> for HungerSystem {
         WriteStorage<'a, HungerClock>,
         ReadExpect<'a, Entity>, // The player
         ReadExpect<'a, RunState>,
-        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, EffectQueue>,
         WriteExpect<'a, GameLog>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, runstate, mut inflict_damage, mut log) =
-            data;
+        let (entities, mut hunger_clock, player_entity, runstate, mut effects, mut log) = data;
 
         // 找到有饥饿时钟的实体
         for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
@@ -45,29 +46,33 @@ impl<'a> System<'a> for HungerSystem {
                             clock.state = HungerState::Normal;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are no longer well fed.".to_string());
+                                log.push("You are no longer well fed.");
                             }
                         }
                         HungerState::Normal => {
                             clock.state = HungerState::Hungry;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are hungry.".to_string());
+                                log.push("You are hungry.");
                             }
                         }
                         HungerState::Hungry => {
                             clock.state = HungerState::Starving;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are starving!".to_string());
+                                log.push("You are starving!");
                             }
                         }
                         HungerState::Starving => {
                             // Inflict damage from hunger
                             if entity == *player_entity {
-                                log.entries.push("Your hunger pangs are getting painful! You suffer 1 hp damage.".to_string());
+                                log.push("Your hunger pangs are getting painful! You suffer 1 hp damage.");
                             }
-                            SufferDamage::new_damage(&mut inflict_damage, entity, 1);
+                            effects.add_effect(
+                                None,
+                                EffectType::Damage { amount: 1 },
+                                Targets::Single { target: entity },
+                            );
                         }
                     }
                 }
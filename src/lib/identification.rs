@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::{Name, ObfuscatedName};
+
+// 物品鉴定
+// 卷轴和药水在使用或者被鉴定之前，只显示一个随机的外观名字
+
+// 需要隐藏名字的魔法物品的种类
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum MagicItemClass {
+    Scroll,
+    Potion,
+}
+
+const SCROLL_DESCRIPTIONS: &[&str] = &[
+    "a crumpled scroll",
+    "a tattered scroll",
+    "a faded scroll",
+    "a rune-covered scroll",
+    "a blood-stained scroll",
+    "a wrinkled scroll",
+    "a sealed scroll",
+    "a charred scroll",
+];
+
+const POTION_DESCRIPTIONS: &[&str] = &[
+    "a murky potion",
+    "a bubbling potion",
+    "a glowing potion",
+    "a fizzy potion",
+    "a cloudy potion",
+    "a smoking potion",
+    "an oily potion",
+    "a viscous potion",
+];
+
+// 已知物品的登记表，作为资源插入 world，并随游戏一起保存
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ItemRegistry {
+    // 已经鉴定过的物品的真实名字
+    pub identified: HashSet<String>,
+    // 真实名字 -> 本局游戏中的外观名字
+    pub obfuscated: HashMap<String, String>,
+}
+
+impl ItemRegistry {
+    pub fn new() -> ItemRegistry {
+        ItemRegistry {
+            identified: HashSet::new(),
+            obfuscated: HashMap::new(),
+        }
+    }
+
+    // 得到物品的外观名字，第一次遇到这种物品时随机分配一个还没有被使用的外观
+    pub fn obfuscated_name(
+        &mut self,
+        real_name: &str,
+        class: MagicItemClass,
+        rng: &mut RandomNumberGenerator,
+    ) -> String {
+        if let Some(name) = self.obfuscated.get(real_name) {
+            return name.clone();
+        }
+
+        let descriptions = match class {
+            MagicItemClass::Scroll => SCROLL_DESCRIPTIONS,
+            MagicItemClass::Potion => POTION_DESCRIPTIONS,
+        };
+        let unused: Vec<&&str> = descriptions
+            .iter()
+            .filter(|d| !self.obfuscated.values().any(|used| used == **d))
+            .collect();
+        let name = if unused.is_empty() {
+            match class {
+                MagicItemClass::Scroll => "an unknown scroll".to_string(),
+                MagicItemClass::Potion => "an unknown potion".to_string(),
+            }
+        } else {
+            let roll = rng.roll_dice(1, unused.len() as i32) - 1;
+            unused[roll as usize].to_string()
+        };
+        self.obfuscated.insert(real_name.to_string(), name.clone());
+        name
+    }
+
+    pub fn identify(&mut self, real_name: &str) {
+        self.identified.insert(real_name.to_string());
+    }
+
+    pub fn is_identified(&self, real_name: &str) -> bool {
+        self.identified.contains(real_name)
+    }
+}
+
+// 物品显示给玩家的名字
+pub fn item_display_name(
+    name: &Name,
+    obfuscated: Option<&ObfuscatedName>,
+    registry: &ItemRegistry,
+) -> String {
+    match obfuscated {
+        Some(obfuscated) if !registry.is_identified(&name.name) => obfuscated.name.clone(),
+        _ => name.name.clone(),
+    }
+}
+
+pub fn get_item_display_name(ecs: &World, item: Entity) -> String {
+    let names = ecs.read_storage::<Name>();
+    let obfuscated = ecs.read_storage::<ObfuscatedName>();
+    let registry = ecs.fetch::<ItemRegistry>();
+    match names.get(item) {
+        Some(name) => item_display_name(name, obfuscated.get(item), &registry),
+        None => "Unnamed item".to_string(),
+    }
+}
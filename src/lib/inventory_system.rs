@@ -23,30 +23,108 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, ObfuscatedName>,
+        ReadExpect<'a, ItemRegistry>,
+        Entities<'a>,
+        WriteStorage<'a, Quantity>,
+        ReadStorage<'a, Weight>,
+        WriteStorage<'a, Burden>,
+        ReadStorage<'a, Gold>,
+        WriteStorage<'a, Wallet>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            obfuscated,
+            registry,
+            entities,
+            mut quantities,
+            weights,
+            mut burdens,
+            gold,
+            mut wallets,
+        ) = data;
 
         // 拾取 物品后 查找 有这些组件的实体
         for pickup in wants_pickup.join() {
+            // 金币直接放进钱包
+            if let (Some(gold), Some(wallet)) =
+                (gold.get(pickup.item), wallets.get_mut(pickup.collected_by))
+            {
+                wallet.gold += gold.amount;
+                positions.remove(pickup.item);
+                entities.delete(pickup.item).expect("Unable to delete gold");
+                if pickup.collected_by == *player_entity {
+                    gamelog.push(format!("You pick up {} gold.", gold.amount));
+                }
+                continue;
+            }
+
+            // 超过负重上限太多 就拿不起来了
+            if let Some(burden) = burdens.get_mut(pickup.collected_by) {
+                let amount = quantities.get(pickup.item).map_or(1, |q| q.amount);
+                let item_weight =
+                    weights.get(pickup.item).map_or(0.0, |w| w.weight) * amount as f32;
+                if burden.carried + item_weight > burden.capacity * MAX_CARRY_MULTIPLIER {
+                    if pickup.collected_by == *player_entity {
+                        gamelog.push(format!(
+                            "You cannot carry {}, it is too heavy.",
+                            item_display_name(
+                                names.get(pickup.item).unwrap(),
+                                obfuscated.get(pickup.item),
+                                &registry
+                            )
+                        ));
+                    }
+                    continue;
+                }
+                burden.carried += item_weight;
+            }
+
             // 实体移除position 组件, 该实体 移除 position 组件后, 会从地图上消失, 因为渲染的迭代的存储器 是包含 position 组件存储器的
             positions.remove(pickup.item);
-            // 插入InBackpack 组件
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
+
+            // 背包中已经有同样的物品，合并到那一堆中
+            let stack = find_stack(
+                pickup.item,
+                pickup.collected_by,
+                &entities,
+                &names,
+                &backpack,
+                &quantities,
+            );
+            if let Some(stack) = stack {
+                let amount = quantities.get(pickup.item).unwrap().amount;
+                quantities.get_mut(stack).unwrap().amount += amount;
+                entities
+                    .delete(pickup.item)
+                    .expect("Unable to delete stacked item");
+            } else {
+                // 插入InBackpack 组件
+                backpack
+                    .insert(
+                        pickup.item,
+                        InBackpack {
+                            owner: pickup.collected_by,
+                        },
+                    )
+                    .expect("Unable to insert backpack entry");
+            }
 
             if pickup.collected_by == *player_entity {
                 // 打印拾取
-                gamelog.entries.push(format!(
-                    "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name
+                gamelog.push(format!(
+                    "You pick up {}.",
+                    item_display_name(
+                        names.get(pickup.item).unwrap(),
+                        obfuscated.get(pickup.item),
+                        &registry
+                    )
                 ));
             }
         }
@@ -57,321 +135,164 @@ impl<'a> System<'a> for ItemCollectionSystem {
 }
 
 // 物品使用 系统 药水使用系统 组件，想要饮用药水
+// 物品的实际效果交给 effects 处理，这里只负责 决定目标 和 装备物品
 pub struct ItemUseSystem {}
 impl<'a> System<'a> for ItemUseSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
-        WriteExpect<'a, Map>,
+        ReadExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Name>,
-        ReadStorage<'a, Consumable>,
-        ReadStorage<'a, ProvidesHealing>,
-        ReadStorage<'a, InflictsDamage>,
-        WriteStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
-        WriteStorage<'a, HungerClock>, // 写 mut
-        ReadStorage<'a, ProvidesFood>, // 读
-        ReadStorage<'a, MagicMapper>,
-        WriteExpect<'a, RunState>,
+        WriteExpect<'a, EffectQueue>,
+        ReadStorage<'a, Cursed>,
+        WriteExpect<'a, ItemRegistry>,
+        ReadStorage<'a, Charges>,
+        ReadStorage<'a, TwoHanded>,
     );
 
-    #[allow(clippy::cognitive_complexity)]
     fn run(&mut self, data: Self::SystemData) {
         let (
             player_entity,
             mut gamelog,
-            mut map,
+            map,
             entities,
             mut wants_use,
             names,
-            consumables,
-            healing,
-            inflict_damage,
-            mut combat_stats,
-            mut suffer_damage,
             aoe,
-            mut confused,
             equippable,
             mut equipped,
             mut backpack,
-            mut particle_builder,
-            positions,
-            mut hunger_clocks,
-            provides_food,
-            magic_mapper,
-            mut runstate,
+            mut effects,
+            cursed,
+            mut registry,
+            charges,
+            two_handed_items,
         ) = data;
-        // 迭代所有的 WantsToDrinkPotion 的意图对象，
+        // 迭代所有的 WantsToUseItem 的意图对象，
         for (entity, useitem) in (&entities, &wants_use).join() {
-            // 物体是否使用的标志
-            let mut used_item = true;
-
-            // Targeting 存储待攻击 实体
-            let mut targets: Vec<Entity> = Vec::new();
-            match useitem.target {
-                None => {
-                    // if　there is no target, apply it to the player
-                    targets.push(*player_entity);
-                }
-                Some(target) => {
-                    // 使用的物品 是否 包含 AOE 组件
-                    let area_effect = aoe.get(useitem.item);
-                    match area_effect {
-                        None => {
-                            // Single target in tile 单体
-                            let idx = map.xy_idx(target.x, target.y);
-                            // 遍历 tile contain
-                            for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
-                            }
-                        }
-                        Some(area_effect) => {
-                            // Aoe blast 爆炸 一个 范围
-                            let mut blast_tiles =
-                                rltk::field_of_view(target, area_effect.radius, &*map);
-                            // Retains 保留
-                            blast_tiles.retain(|p| {
-                                p.x > 0 && p.x < map.width - 1 && p.y > 0 && p.y < map.height - 1
-                            });
-                            // 遍历 这个 范围的tile
-                            for tile_idx in blast_tiles.iter() {
-                                let idx = map.xy_idx(tile_idx.x, tile_idx.y);
-                                for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
-                                }
-                                // if an effect hits a whole area, it would be good to indicate waht the area is, in the targeting section of the system
-                                particle_builder.request(
-                                    tile_idx.x,
-                                    tile_idx.y,
-                                    rltk::RGB::named(rltk::ORANGE),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('░'),
-                                    200.0,
-                                );
-                            }
-                        }
+            // 充能用完的物品 无法使用，但仍然消耗这一回合
+            if let Some(charges) = charges.get(useitem.item) {
+                if charges.current < 1 {
+                    if entity == *player_entity {
+                        gamelog.push(format!(
+                            "{} has no charges left.",
+                            names.get(useitem.item).unwrap().name
+                        ));
                     }
+                    continue;
                 }
             }
 
+            // Targeting
+            let targets = match useitem.target {
+                // if　there is no target, apply it to the user
+                None => Targets::Single { target: entity },
+                Some(target) => match aoe.get(useitem.item) {
+                    // Single target in tile 单体
+                    None => Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y) as i32,
+                    },
+                    // Aoe blast 爆炸 一个 范围
+                    Some(area_effect) => Targets::Area {
+                        center: target,
+                        radius: area_effect.radius,
+                    },
+                },
+            };
+
             // if it is equippable, then we want to equip it -  and unequip whatever else in that slot
             let item_equippable = equippable.get(useitem.item);
             match item_equippable {
                 None => {}
                 Some(can_equip) => {
                     let target_slot = can_equip.slot;
-                    let target = targets[0];
+                    let target = entity;
+                    let two_handed = two_handed_items.get(useitem.item).is_some();
+                    // 主手 和 副手 两个槽
+                    let is_hand = |slot: EquipmentSlot| {
+                        slot == EquipmentSlot::Melee || slot == EquipmentSlot::Shield
+                    };
 
                     // Remove any items the target has in the item's slot 移除目标物品槽中的任何物品
                     let mut to_unequip: Vec<Entity> = Vec::new();
-                    for (item_entity, already_equipped, name) in
-                        (&entities, &equipped, &names).join()
-                    {
+                    // 槽中被诅咒的装备无法卸下，也就无法装备新的物品
+                    let mut stuck_item: Option<Entity> = None;
+                    for (item_entity, already_equipped) in (&entities, &equipped).join() {
                         // 如果 这个item_entity 被装备，将其卸下放到to_unequip 列表 中
-                        if already_equipped.owner == target && already_equipped.slot == target_slot
-                        {
-                            to_unequip.push(item_entity);
-                            if target == *player_entity {
-                                gamelog.entries.push(format!("You unequip {}.", name.name));
-                            }
-                        }
-                    }
-                    // 修改to_unequip 中的实体的组件信息，表示这个实体被放到背包中
-                    for item in to_unequip.iter() {
-                        equipped.remove(*item);
-                        backpack
-                            .insert(*item, InBackpack { owner: target })
-                            .expect("Unable to insert backpack entry");
-                    }
-
-                    // Wield使用 the item
-                    equipped
-                        .insert(
-                            useitem.item,
-                            Equipped {
-                                owner: target,
-                                slot: target_slot,
-                            },
-                        )
-                        .expect("Unable to insert equipped component");
-                    backpack.remove(useitem.item);
-
-                    if target == *player_entity {
-                        gamelog.entries.push(format!(
-                            "You equip {}.",
-                            names.get(useitem.item).unwrap().name
-                        ));
-                    }
-                }
-            }
-            // if its a magic mapper...
-            let is_mapper = magic_mapper.get(useitem.item);
-            match is_mapper {
-                Some(_) => {
-                    used_item = true;
-                    used_item = true;
-                    gamelog
-                        .entries
-                        .push("The map is revealed to you!".to_string());
-                    *runstate = RunState::MagicMapReveal { row: 0 };
-                }
-                None => {}
-            }
-            // if it heals, apply the healing
-            let item_heals = healing.get(useitem.item);
-            match item_heals {
-                None => {}
-
-                Some(healer) => {
-                    used_item = false;
-                    for target in targets.iter() {
-                        // target 是一个实体，存储组件获得这个实体 对应这个组件的属性数据
-                        let stats = combat_stats.get_mut(*target);
-                        if let Some(stats) = stats {
-                            // heals up the drinker 喝了药水的生命值 最大时生命值的最大值
-                            stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
-                            // 如果时玩家 喝了药水，打印日志
-                            if entity == *player_entity {
-                                gamelog.entries.push(format!(
-                                    "you drink the {}, healing {} hp.",
-                                    names.get(useitem.item).unwrap().name,
-                                    healer.heal_amount
-                                ));
-                                used_item = true;
-                                // start by showing a heart when you drink a healing potion
-                                // 在喝下药水的实体显示一颗心
-                                let pos = positions.get(*target);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::GREEN),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('♥'),
-                                        200.0,
-                                    );
-                                }
+                        // 双手武器 和 主手副手中的任何装备 都冲突
+                        let conflict = already_equipped.slot == target_slot
+                            || (two_handed && is_hand(already_equipped.slot))
+                            || (two_handed_items.get(item_entity).is_some()
+                                && is_hand(target_slot));
+                        if already_equipped.owner == target && conflict {
+                            if cursed.get(item_entity).is_some() {
+                                stuck_item = Some(item_entity);
+                            } else {
+                                to_unequip.push(item_entity);
                             }
                         }
                     }
-                }
-            }
 
-            // it is edible, eat it
-            let item_edible = provides_food.get(useitem.item);
-            match item_edible {
-                None => {}
-                Some(_) => {
-                    used_item = true;
-                    let target = targets[0];
-                    let hc = hunger_clocks.get_mut(target);
-                    if let Some(hc) = hc {
-                        hc.state = HungerState::WellFed;
-                        hc.duration = 20;
-                        gamelog.entries.push(format!(
-                            "You eat the {}.",
-                            names.get(useitem.item).unwrap().name
-                        ));
-                    }
-                }
-            }
-            // if it inflicts(给予) 伤害，apply it to the target cell
-            // This checks to see if we have an InflictsDamage component on the item - and if it does, applies the damage to everyone in the targeted cell.
-            let item_damage = inflict_damage.get(useitem.item);
-            match item_damage {
-                None => {}
-                Some(damage) => {
-                    // 是否使用used_item
-                    used_item = false;
-                    // 遍历
-                    for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
-                        if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(useitem.item).unwrap();
-                            gamelog.entries.push(format!(
-                                "You use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
+                    if let Some(stuck_item) = stuck_item {
+                        if target == *player_entity {
+                            gamelog.push(format!(
+                                "You cannot unequip {}, it is cursed!",
+                                names.get(stuck_item).unwrap().name
                             ));
-                            let pos = positions.get(*mob);
-                            if let Some(pos) = pos {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    rltk::RGB::named(rltk::RED),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('‼'),
-                                    200.0,
-                                );
-                            }
                         }
-
-                        used_item = true;
-                    }
-                }
-            }
-
-            // Can it pass along confusion? Note the use of scopes to escape from the borrow checker!
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(useitem.item);
-                match causes_confusion {
-                    None => {}
-                    Some(confusion) => {
-                        used_item = false;
-                        for mob in targets.iter() {
-                            // 加入到 confusion 向量
-                            add_confusion.push((*mob, confusion.turns));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!(
-                                    "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
+                    } else {
+                        // 修改to_unequip 中的实体的组件信息，表示这个实体被放到背包中
+                        for item in to_unequip.iter() {
+                            equipped.remove(*item);
+                            backpack
+                                .insert(*item, InBackpack { owner: target })
+                                .expect("Unable to insert backpack entry");
+                            if target == *player_entity {
+                                gamelog.push(format!(
+                                    "You unequip {}.",
+                                    names.get(*item).unwrap().name
                                 ));
                             }
+                        }
 
-                            let pos = positions.get(*mob);
-                            if let Some(pos) = pos {
-                                particle_builder.request(
-                                    pos.x,
-                                    pos.y,
-                                    rltk::RGB::named(rltk::MAGENTA),
-                                    rltk::RGB::named(rltk::BLACK),
-                                    rltk::to_cp437('?'),
-                                    200.0,
-                                );
+                        // Wield使用 the item
+                        equipped
+                            .insert(
+                                useitem.item,
+                                Equipped {
+                                    owner: target,
+                                    slot: target_slot,
+                                },
+                            )
+                            .expect("Unable to insert equipped component");
+                        backpack.remove(useitem.item);
+
+                        if target == *player_entity {
+                            // 装备上之后就知道这件装备是什么了
+                            let name = &names.get(useitem.item).unwrap().name;
+                            registry.identify(name);
+                            gamelog.push(format!("You equip {}.", name));
+                            if cursed.get(useitem.item).is_some() {
+                                gamelog.push(format!("You feel a chill as {} binds to you!", name));
                             }
                         }
                     }
                 }
             }
-            // 被confusion 的 攻击列表的对象
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
-            }
-            // If its a consumable, we delete it on use
-            if used_item {
-                let consumable = consumables.get(useitem.item);
-                match consumable {
-                    None => {}
-                    Some(_) => {
-                        entities.delete(useitem.item).expect("Delete failed");
-                    }
-                }
-            }
+
+            // 物品的 治疗 伤害 昏迷 食物 魔法地图 等效果，都放进效果队列
+            effects.add_effect(
+                Some(entity),
+                EffectType::ItemUse { item: useitem.item },
+                targets,
+            );
         }
 
         wants_use.clear();
@@ -391,6 +312,8 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, ObfuscatedName>,
+        ReadExpect<'a, ItemRegistry>,
     );
     fn run(&mut self, data: Self::SystemData) {
         let (
@@ -401,6 +324,8 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            obfuscated,
+            registry,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -426,9 +351,13 @@ impl<'a> System<'a> for ItemDropSystem {
             backpack.remove(to_drop.item);
 
             if entity == *player_entity {
-                gamelog.entries.push(format!(
-                    "You drop the {}.",
-                    names.get(to_drop.item).unwrap().name
+                gamelog.push(format!(
+                    "You drop {}.",
+                    item_display_name(
+                        names.get(to_drop.item).unwrap(),
+                        obfuscated.get(to_drop.item),
+                        &registry
+                    )
                 ));
             }
         }
@@ -447,11 +376,34 @@ impl<'a> System<'a> for ItemRemoveSystem {
         WriteStorage<'a, WantsToRemoveItem>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Cursed>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack) = data;
+        let (
+            entities,
+            mut wants_remove,
+            mut equipped,
+            mut backpack,
+            player_entity,
+            mut gamelog,
+            names,
+            cursed,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            // 被诅咒的装备无法卸下
+            if cursed.get(to_remove.item).is_some() {
+                if entity == *player_entity {
+                    gamelog.push(format!(
+                        "You cannot remove {}, it is cursed!",
+                        names.get(to_remove.item).unwrap().name
+                    ));
+                }
+                continue;
+            }
             // 从equipped 组件存储器移除这个实体
             equipped.remove(to_remove.item);
             // 将这个实体加入到equipped 组件存储器
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+
+use rltk::VirtualKeyCode;
+use serde::{Deserialize, Serialize};
+
+// 按键绑定
+// 玩家的输入先被翻译成 动作，再由 player_input 处理，按键可以在配置文件中重新绑定
+
+// 用户的按键配置文件，没有这个文件时 使用默认的按键
+pub const KEYMAP_FILE: &str = "./keybindings.json";
+
+// 玩家可以执行的动作
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Action {
+    MoveWest,
+    MoveEast,
+    MoveNorth,
+    MoveSouth,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    Wait,
+    PickUp,
+    Inventory,
+    Drop,
+    RemoveItem,
+    Equipment,
+    Crafting,
+    CastSpell,
+    Search,
+    Disarm,
+    Fire,
+    Throw,
+    MessageLog,
+    Character,
+    Look,
+    Descend,
+    TravelToStairs,
+    AutoExplore,
+    Help,
+    SaveAndQuit,
+}
+
+impl Action {
+    // 帮助界面中 按这个顺序列出
+    pub const ALL: [Action; 28] = [
+        Action::MoveWest,
+        Action::MoveEast,
+        Action::MoveNorth,
+        Action::MoveSouth,
+        Action::MoveNorthEast,
+        Action::MoveNorthWest,
+        Action::MoveSouthEast,
+        Action::MoveSouthWest,
+        Action::Wait,
+        Action::PickUp,
+        Action::Inventory,
+        Action::Drop,
+        Action::RemoveItem,
+        Action::Equipment,
+        Action::Crafting,
+        Action::CastSpell,
+        Action::Search,
+        Action::Disarm,
+        Action::Fire,
+        Action::Throw,
+        Action::MessageLog,
+        Action::Character,
+        Action::Look,
+        Action::Descend,
+        Action::TravelToStairs,
+        Action::AutoExplore,
+        Action::Help,
+        Action::SaveAndQuit,
+    ];
+
+    // 移动的方向
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            Action::MoveWest => Some((-1, 0)),
+            Action::MoveEast => Some((1, 0)),
+            Action::MoveNorth => Some((0, -1)),
+            Action::MoveSouth => Some((0, 1)),
+            Action::MoveNorthEast => Some((1, -1)),
+            Action::MoveNorthWest => Some((-1, -1)),
+            Action::MoveSouthEast => Some((1, 1)),
+            Action::MoveSouthWest => Some((-1, 1)),
+            _ => None,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::MoveWest => "Move west",
+            Action::MoveEast => "Move east",
+            Action::MoveNorth => "Move north",
+            Action::MoveSouth => "Move south",
+            Action::MoveNorthEast => "Move north-east",
+            Action::MoveNorthWest => "Move north-west",
+            Action::MoveSouthEast => "Move south-east",
+            Action::MoveSouthWest => "Move south-west",
+            Action::Wait => "Wait a turn",
+            Action::PickUp => "Pick up an item",
+            Action::Inventory => "Use an item",
+            Action::Drop => "Drop an item",
+            Action::RemoveItem => "Remove equipment",
+            Action::Equipment => "Show equipment",
+            Action::Crafting => "Craft an item",
+            Action::CastSpell => "Cast a spell",
+            Action::Search => "Search for traps",
+            Action::Disarm => "Disarm a trap",
+            Action::Fire => "Fire a ranged weapon",
+            Action::Throw => "Throw an item",
+            Action::MessageLog => "Message log",
+            Action::Character => "Character sheet",
+            Action::Look => "Look around",
+            Action::Descend => "Go down the stairs",
+            Action::TravelToStairs => "Travel to the stairs",
+            Action::AutoExplore => "Explore automatically",
+            Action::Help => "Show this help",
+            Action::SaveAndQuit => "Save and quit",
+        }
+    }
+}
+
+// 配置文件中可以使用的按键，名字和 VirtualKeyCode 的变体名相同
+const KEYS: &[VirtualKeyCode] = &[
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+    VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2,
+    VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4,
+    VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8,
+    VirtualKeyCode::Numpad9,
+    VirtualKeyCode::F1,
+    VirtualKeyCode::F2,
+    VirtualKeyCode::F3,
+    VirtualKeyCode::F4,
+    VirtualKeyCode::F5,
+    VirtualKeyCode::F6,
+    VirtualKeyCode::F7,
+    VirtualKeyCode::F8,
+    VirtualKeyCode::F9,
+    VirtualKeyCode::F10,
+    VirtualKeyCode::F11,
+    VirtualKeyCode::F12,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::Insert,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Period,
+    VirtualKeyCode::Comma,
+    VirtualKeyCode::Slash,
+    VirtualKeyCode::Backslash,
+    VirtualKeyCode::Semicolon,
+    VirtualKeyCode::Apostrophe,
+    VirtualKeyCode::Minus,
+    VirtualKeyCode::Equals,
+    VirtualKeyCode::LBracket,
+    VirtualKeyCode::RBracket,
+    VirtualKeyCode::Grave,
+];
+
+pub fn key_name(key: VirtualKeyCode) -> String {
+    format!("{:?}", key)
+}
+
+fn parse_key(name: &str) -> Option<VirtualKeyCode> {
+    KEYS.iter()
+        .copied()
+        .find(|key| key_name(*key).eq_ignore_ascii_case(name))
+}
+
+// 当前的按键绑定，作为资源插入 ecs 中
+pub struct KeyMap {
+    bindings: HashMap<Action, Vec<VirtualKeyCode>>,
+}
+
+impl KeyMap {
+    // 默认的按键：方向键、小键盘 和 vi 键
+    pub fn default_keys() -> KeyMap {
+        use VirtualKeyCode::*;
+        let defaults = vec![
+            (Action::MoveWest, vec![Left, Numpad4, H]),
+            (Action::MoveEast, vec![Right, Numpad6, L]),
+            (Action::MoveNorth, vec![Up, Numpad8, K]),
+            (Action::MoveSouth, vec![Down, Numpad2, J]),
+            (Action::MoveNorthEast, vec![Numpad9, Y]),
+            (Action::MoveNorthWest, vec![Numpad7, U]),
+            (Action::MoveSouthEast, vec![Numpad3, N]),
+            (Action::MoveSouthWest, vec![Numpad1, B]),
+            (Action::Wait, vec![Numpad5, Space]),
+            (Action::PickUp, vec![G]),
+            (Action::Inventory, vec![I]),
+            (Action::Drop, vec![D]),
+            (Action::RemoveItem, vec![R]),
+            (Action::Equipment, vec![E]),
+            (Action::Crafting, vec![C]),
+            (Action::CastSpell, vec![Z]),
+            (Action::Search, vec![S]),
+            (Action::Disarm, vec![A]),
+            (Action::Fire, vec![F]),
+            (Action::Throw, vec![T]),
+            (Action::MessageLog, vec![M]),
+            (Action::Character, vec![P]),
+            (Action::Look, vec![X]),
+            (Action::Descend, vec![Period]),
+            (Action::TravelToStairs, vec![Comma]),
+            (Action::AutoExplore, vec![O]),
+            (Action::Help, vec![Slash, F1]),
+            (Action::SaveAndQuit, vec![Escape]),
+        ];
+        KeyMap {
+            bindings: defaults.into_iter().collect(),
+        }
+    }
+
+    // 按键对应的动作
+    pub fn action(&self, key: VirtualKeyCode) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|action| self.keys(*action).contains(&key))
+    }
+
+    pub fn keys(&self, action: Action) -> &[VirtualKeyCode] {
+        self.bindings
+            .get(&action)
+            .map_or(&[], |keys| keys.as_slice())
+    }
+
+    // 配置文件中出现的动作 替换默认的按键，没有出现的动作 保留默认的按键
+    // 配置文件中绑定的按键 会从其它动作的默认按键中移除，比如把 A 绑定给 MoveWest 之后，Disarm 就没有按键了
+    // 配置文件的格式：{ "MoveWest": ["Left", "A"], "Wait": ["Period"] }
+    pub fn from_json(json: &str) -> Result<KeyMap, String> {
+        let config: HashMap<Action, Vec<String>> = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid key bindings in {}: {}", KEYMAP_FILE, e))?;
+
+        let mut keymap = KeyMap::default_keys();
+        let configured: Vec<Action> = config.keys().copied().collect();
+        for (action, names) in config {
+            let mut keys = Vec::new();
+            for name in names {
+                match parse_key(&name) {
+                    Some(key) => keys.push(key),
+                    None => {
+                        return Err(format!(
+                            "Unknown key \"{}\" bound to {:?} in {}",
+                            name, action, KEYMAP_FILE
+                        ))
+                    }
+                }
+            }
+            for (other, other_keys) in keymap.bindings.iter_mut() {
+                if !configured.contains(other) {
+                    other_keys.retain(|key| !keys.contains(key));
+                }
+            }
+            keymap.bindings.insert(action, keys);
+        }
+
+        // 配置文件中 一个按键 不能同时绑定两个动作
+        for (i, first) in Action::ALL.iter().enumerate() {
+            for second in Action::ALL.iter().skip(i + 1) {
+                if let Some(key) = keymap
+                    .keys(*first)
+                    .iter()
+                    .find(|key| keymap.keys(*second).contains(key))
+                {
+                    return Err(format!(
+                        "Key {} is bound to both {:?} and {:?} in {}",
+                        key_name(*key),
+                        first,
+                        second,
+                        KEYMAP_FILE
+                    ));
+                }
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+// 读取用户的按键配置，没有配置文件时 使用默认的按键
+pub fn load_keymap() -> Result<KeyMap, String> {
+    match fs::read_to_string(KEYMAP_FILE) {
+        Ok(json) => KeyMap::from_json(&json),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(KeyMap::default_keys()),
+        Err(e) => Err(format!("Unable to read {}: {}", KEYMAP_FILE, e)),
+    }
+}
@@ -19,6 +19,35 @@ pub enum TileType {
     Wall,  // “#”符号
     Floor, // “.” 符号
     DownStairs,
+    // 城镇中的地面
+    Road,
+    Grass,
+    WoodFloor,
+    // 机关控制的门，关上时 挡住视线和去路
+    ClosedDoor,
+    OpenDoor,
+    // 被机关淹没的地面
+    Water,
+}
+
+// 这种 tile 是否挡住去路 和 视线
+pub fn tile_blocks(tile: TileType) -> bool {
+    matches!(tile, TileType::Wall | TileType::ClosedDoor)
+}
+
+// 观察模式中 tile 的描述
+pub fn tile_description(tile: TileType) -> &'static str {
+    match tile {
+        TileType::Wall => "a solid stone wall",
+        TileType::Floor => "a rough stone floor",
+        TileType::DownStairs => "a staircase leading further down",
+        TileType::Road => "a well trodden dirt road",
+        TileType::Grass => "a patch of grass",
+        TileType::WoodFloor => "a creaking wooden floor",
+        TileType::ClosedDoor => "a closed door",
+        TileType::OpenDoor => "an open door",
+        TileType::Water => "knee-deep water",
+    }
 }
 // 创建一个map struct ，存储与map 相关的数据
 // 使用 宏 让Map 进行序列化
@@ -63,7 +92,7 @@ impl Map {
     // 填充(populate)被阻挡的tile  wall was blocked
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::Wall;
+            self.blocked[i] = tile_blocks(*tile);
         }
     }
 
@@ -99,8 +128,8 @@ impl Map {
 impl BaseMap for Map {
     // opaque 不透明的
     fn is_opaque(&self, idx: usize) -> bool {
-        // 如果图块时墙，返回true 否则 返回 false
-        self.tiles[idx as usize] == TileType::Wall
+        // 如果图块时墙 或者关着的门，返回true 否则 返回 false
+        tile_blocks(self.tiles[idx as usize])
     }
 
     // 根据 tilt 索引 得到 这两个tile 之间的距离
@@ -237,6 +266,31 @@ pub fn draw_map(map : &Map, ctx : &mut Rltk) {
                     glyph = rltk::to_cp437('>');
                     fg = RGB::from_f32(0., 1.0, 1.0);
                 }
+                TileType::Road => {
+                    glyph = rltk::to_cp437('≡');
+                    fg = RGB::named(rltk::GRAY);
+                }
+                TileType::Grass => {
+                    // '"' 是护身符的字符，草地用 ',' 以免看起来像物品
+                    glyph = rltk::to_cp437(',');
+                    fg = RGB::named(rltk::GREEN);
+                }
+                TileType::WoodFloor => {
+                    glyph = rltk::to_cp437('░');
+                    fg = RGB::named(rltk::CHOCOLATE);
+                }
+                TileType::ClosedDoor => {
+                    glyph = rltk::to_cp437('+');
+                    fg = RGB::named(rltk::CHOCOLATE);
+                }
+                TileType::OpenDoor => {
+                    glyph = rltk::to_cp437('\'');
+                    fg = RGB::named(rltk::CHOCOLATE);
+                }
+                TileType::Water => {
+                    glyph = rltk::to_cp437('~');
+                    fg = RGB::named(rltk::BLUE);
+                }
             }
             // 渲染血迹
             if map.bloodstains.contains(&idx) {
@@ -1,6 +1,4 @@
-use super::{
-    apply_room_to_map, spawner, Map, MapBuilder, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER,
-};
+use super::{apply_room_to_map, spawner, Map, MapBuilder, MapHistory, Position, Rect, TileType};
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 
@@ -9,7 +7,7 @@ pub struct BspDungeonBuilder {
     starting_position: Position,
     depth: i32,
     rooms: Vec<Rect>,
-    history: Vec<Map>,
+    history: MapHistory,
     rects: Vec<Rect>,
 }
 
@@ -20,7 +18,7 @@ impl BspDungeonBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             rooms: Vec::new(),
-            history: Vec::new(),
+            history: MapHistory::default(),
             rects: Vec::new(),
         }
     }
@@ -224,7 +222,7 @@ impl MapBuilder for BspDungeonBuilder {
         self.starting_position.clone()
     }
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
     fn build_map(&mut self) {
         self.build();
@@ -234,13 +232,7 @@ impl MapBuilder for BspDungeonBuilder {
             spawner::spawn_room(ecs, room, self.depth);
         }
     }
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
@@ -1,6 +1,4 @@
-use super::{
-    apply_room_to_map, spawner, Map, MapBuilder, Position, Rect, TileType, SHOW_MAPGEN_VISUALIZER,
-};
+use super::{apply_room_to_map, spawner, Map, MapBuilder, MapHistory, Position, Rect, TileType};
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 // 这种类型的地牢可以代表一个内部空间，可能是一艘太空船、一座城堡，甚至是一个家。
@@ -11,7 +9,7 @@ pub struct BspInteriorBuilder {
     starting_position: Position,
     depth: i32,
     rooms: Vec<Rect>,
-    history: Vec<Map>,
+    history: MapHistory,
     rects: Vec<Rect>,
 }
 
@@ -25,7 +23,7 @@ impl MapBuilder for BspInteriorBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self) {
@@ -38,14 +36,8 @@ impl MapBuilder for BspInteriorBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 
@@ -56,7 +48,7 @@ impl BspInteriorBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             rooms: Vec::new(),
-            history: Vec::new(),
+            history: MapHistory::default(),
             rects: Vec::new(),
         }
     }
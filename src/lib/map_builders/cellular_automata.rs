@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use super::{
     apply_room_to_map, generate_voronoi_spawn_regions,
-    remove_unreachable_areas_returning_most_distant, spawner, Map, MapBuilder, Position, Rect,
-    TileType, SHOW_MAPGEN_VISUALIZER,
+    remove_unreachable_areas_returning_most_distant, spawner, Map, MapBuilder, MapHistory,
+    Position, Rect, TileType,
 };
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -14,7 +14,7 @@ pub struct CellularAutomataBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas: HashMap<i32, Vec<usize>>,
 }
 
@@ -28,7 +28,7 @@ impl MapBuilder for CellularAutomataBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
     fn build_map(&mut self) {
         self.build();
@@ -41,14 +41,8 @@ impl MapBuilder for CellularAutomataBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 impl CellularAutomataBuilder {
@@ -57,7 +51,7 @@ impl CellularAutomataBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
         }
     }
@@ -7,6 +7,24 @@ use std::{
 
 // 通用代码
 
+// 地图生成过程的快照，用来显示可视化的地图生成
+// 关闭可视化时 不保存快照，避免每一步都复制整张地图
+#[derive(Default)]
+pub struct MapHistory {
+    pub enabled: bool,
+    pub snapshots: Vec<Map>,
+}
+
+impl MapHistory {
+    // 快照中的地图 全部显示出来
+    pub fn push(&mut self, mut snapshot: Map) {
+        for v in snapshot.revealed_tiles.iter_mut() {
+            *v = true;
+        }
+        self.snapshots.push(snapshot);
+    }
+}
+
 // Symmetry 对称性
 #[derive(PartialEq, Copy, Clone)]
 pub enum DLASymmetry {
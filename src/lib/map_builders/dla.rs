@@ -1,6 +1,6 @@
 use super::{
-    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
-    MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
+    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map, MapHistory,
+    MapBuilder, Position, TileType,
 };
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -13,7 +13,7 @@ pub struct DLABuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas: HashMap<i32, Vec<usize>>,
     // 支持三种算法
     algorithm : DLAAlgorithm,
@@ -34,7 +34,7 @@ impl MapBuilder for DLABuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self) {
@@ -47,14 +47,8 @@ impl MapBuilder for DLABuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 
@@ -64,7 +58,7 @@ impl DLABuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             algorithm: DLAAlgorithm::WalkInwards,
             brush_size: 2,
@@ -78,7 +72,7 @@ impl DLABuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             algorithm: DLAAlgorithm::WalkInwards,
             brush_size: 1,
@@ -92,7 +86,7 @@ impl DLABuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             algorithm: DLAAlgorithm::WalkOutwards,
             brush_size: 2,
@@ -106,7 +100,7 @@ impl DLABuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             algorithm: DLAAlgorithm::CentralAttractor,
             brush_size: 2,
@@ -120,7 +114,7 @@ impl DLABuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             algorithm: DLAAlgorithm::CentralAttractor,
             brush_size: 2,
@@ -1,6 +1,6 @@
 use super::{
-    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
-    MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
+    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map, MapHistory,
+    MapBuilder, Position, TileType,
 };
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -27,7 +27,7 @@ pub struct DrunkardsWalkBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas: HashMap<i32, Vec<usize>>,
     settings: DrunkardSettings,
 }
@@ -42,7 +42,7 @@ impl MapBuilder for DrunkardsWalkBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self) {
@@ -55,14 +55,8 @@ impl MapBuilder for DrunkardsWalkBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 impl DrunkardsWalkBuilder {
@@ -71,7 +65,7 @@ impl DrunkardsWalkBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
             settings,
         }
@@ -82,7 +76,7 @@ impl DrunkardsWalkBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::StartingPoint,
@@ -98,7 +92,7 @@ impl DrunkardsWalkBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
@@ -114,7 +108,7 @@ impl DrunkardsWalkBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
             settings: DrunkardSettings {
                 spawn_mode: DrunkSpawnMode::Random,
@@ -132,7 +126,7 @@ impl DrunkardsWalkBuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             settings : DrunkardSettings{
                 spawn_mode: DrunkSpawnMode::Random,
@@ -150,7 +144,7 @@ impl DrunkardsWalkBuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             settings : DrunkardSettings{
                 spawn_mode: DrunkSpawnMode::Random,
@@ -1,6 +1,6 @@
 use super::{
     generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
-    MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
+    MapBuilder, MapHistory, Position, TileType,
 };
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -10,7 +10,7 @@ pub struct MazeBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas: HashMap<i32, Vec<usize>>,
 }
 impl MapBuilder for MazeBuilder {
@@ -23,7 +23,7 @@ impl MapBuilder for MazeBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self) {
@@ -36,14 +36,8 @@ impl MapBuilder for MazeBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 
@@ -53,7 +47,7 @@ impl MazeBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
         }
     }
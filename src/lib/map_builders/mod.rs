@@ -6,7 +6,7 @@ mod simple_map;
 use simple_map::SimpleMapBuilder;
 
 mod common;
-use super::{spawner, Rect, TileType, SHOW_MAPGEN_VISUALIZER};
+use super::{spawner, Rect, TileType};
 use common::*;
 use specs::prelude::*;
 
@@ -20,6 +20,10 @@ mod drunkard;
 use drunkard::*;
 mod maze;
 use maze::*;
+mod town;
+use town::TownBuilder;
+mod prefab_builder;
+use prefab_builder::PrefabBuilder;
 
 use common::*;
 use specs::prelude::*;
@@ -34,14 +38,34 @@ pub trait MapBuilder {
     fn get_map(&self) -> Map;
     fn get_starting_position(&self) -> Position;
     fn get_snapshot_history(&self) -> Vec<Map>;
-    fn take_snapshot(&mut self);
+    fn history(&mut self) -> &mut MapHistory;
+
+    // 记录地图生成的一步，关闭可视化时 不做任何事
+    fn take_snapshot(&mut self) {
+        if self.history().enabled {
+            let snapshot = self.get_map();
+            self.history().push(snapshot);
+        }
+    }
+}
+
+// show_mapgen_visualizer 为 false 时，生成地图的过程中 不保存快照
+pub fn random_builder(new_depth: i32, show_mapgen_visualizer: bool) -> Box<dyn MapBuilder> {
+    let mut builder = level_builder(new_depth);
+    builder.history().enabled = show_mapgen_visualizer;
+    builder
 }
 
-pub fn random_builder(new_depth: i32) -> Box<dyn MapBuilder> {
+fn level_builder(new_depth: i32) -> Box<dyn MapBuilder> {
+    // 第一层是城镇
+    if new_depth == 1 {
+        return Box::new(TownBuilder::new(new_depth));
+    }
+
     // 随机创建不同的地图类型
     let mut rng = rltk::RandomNumberGenerator::new();
     let builder = rng.roll_dice(1, 16);
-    match builder {
+    let builder: Box<dyn MapBuilder> = match builder {
         // This is actually two calls, now: we make a box with Box::new(...), and we place an empty SimpleMapBuilder into the box.
         1 => Box::new(BspDungeonBuilder::new(new_depth)),
         2 => Box::new(BspInteriorBuilder::new(new_depth)),
@@ -59,5 +83,7 @@ pub fn random_builder(new_depth: i32) -> Box<dyn MapBuilder> {
         14 => Box::new(VoronoiCellBuilder::pythagoras(new_depth)),
         15 => Box::new(VoronoiCellBuilder::manhattan(new_depth)),
         _ => Box::new(SimpleMapBuilder::new(new_depth)),
-    }
+    };
+    // 在生成的地图上 可能放一个带机关的预制房间
+    Box::new(PrefabBuilder::vaults(new_depth, builder))
 }
@@ -0,0 +1,298 @@
+use super::{spawner, Map, MapBuilder, MapHistory, Position, TileType};
+use crate::{Mechanism, MechanismAction, Player};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+// 预制的房间（vault），盖在其它生成器做好的地图上
+// 模板中每个字符代表一个 tile 或者一个实体：
+//   '#' 墙          '.' 地面
+//   '+' 门（关着）  '|' 关着怪物的闸门
+//   '%' 会倒塌的墙  ',' 会被淹没的地面
+//   'L' 拉杆        '_' 压力板
+//   'g' 哥布林      'o' 兽人
+//   '!' 治疗药水    '$' 金币
+//   'S' 商人
+// mechanisms 把触发器的字符 和 目标的字符 连起来：
+// 拉杆 或者 压力板 触发时，对所有目标字符所在的 tile 执行动作
+
+pub struct PrefabVault {
+    template: &'static [&'static str],
+    // 从第几层开始出现
+    first_depth: i32,
+    // (触发器的字符, 目标的字符, 触发后的动作)
+    mechanisms: &'static [(char, char, MechanismAction)],
+}
+
+// 压力板 踩一下就把怪物放出来
+#[rustfmt::skip]
+const MONSTER_PEN: PrefabVault = PrefabVault {
+    template: &[
+        ".......",
+        ".#####.",
+        ".#gog|_",
+        ".#####.",
+        ".......",
+    ],
+    first_depth: 2,
+    mechanisms: &[('_', '|', MechanismAction::ReleaseMonsters)],
+};
+
+// 拉杆 控制宝库的门
+#[rustfmt::skip]
+const LEVER_VAULT: PrefabVault = PrefabVault {
+    template: &[
+        ".........",
+        ".###+###.",
+        ".#..!..#.",
+        ".#.$.$.#.",
+        ".#######.",
+        "....L....",
+        ".........",
+    ],
+    first_depth: 2,
+    mechanisms: &[('L', '+', MechanismAction::ToggleDoors)],
+};
+
+// 拉杆 让墙倒塌，露出藏起来的宝物
+#[rustfmt::skip]
+const HIDDEN_CACHE: PrefabVault = PrefabVault {
+    template: &[
+        ".........",
+        ".###%###.",
+        ".#!.$.!#.",
+        ".#######.",
+        "...L.....",
+    ],
+    first_depth: 3,
+    mechanisms: &[('L', '%', MechanismAction::CollapseWalls)],
+};
+
+// 拿金币的时候 踩到压力板，房间被水淹没
+#[rustfmt::skip]
+const FLOODED_ROOM: PrefabVault = PrefabVault {
+    template: &[
+        ".........",
+        ".###.###.",
+        ".#,,,,,#.",
+        ".#,,_,,#.",
+        ".#,,$,,#.",
+        ".#######.",
+        ".........",
+    ],
+    first_depth: 3,
+    mechanisms: &[('_', ',', MechanismAction::Flood)],
+};
+
+// 地下城中的小商店，商人只会出现在 城镇 和 这种房间里
+#[rustfmt::skip]
+const SHOP: PrefabVault = PrefabVault {
+    template: &[
+        ".......",
+        ".##.##.",
+        ".#...#.",
+        ".#.S.#.",
+        ".#...#.",
+        ".#####.",
+        ".......",
+    ],
+    first_depth: 2,
+    mechanisms: &[],
+};
+
+const VAULTS: [PrefabVault; 5] = [MONSTER_PEN, LEVER_VAULT, HIDDEN_CACHE, FLOODED_ROOM, SHOP];
+
+impl PrefabVault {
+    fn width(&self) -> i32 {
+        self.template[0].len() as i32
+    }
+
+    fn height(&self) -> i32 {
+        self.template.len() as i32
+    }
+
+    // 模板中 字符 c 所在的全部位置，(x, y) 是模板左上角在地图上的位置
+    fn positions_of(&self, c: char, x: i32, y: i32) -> Vec<Point> {
+        let mut result = Vec::new();
+        for (ty, row) in self.template.iter().enumerate() {
+            for (tx, ch) in row.chars().enumerate() {
+                if ch == c {
+                    result.push(Point::new(x + tx as i32, y + ty as i32));
+                }
+            }
+        }
+        result
+    }
+}
+
+// 包装另一个生成器，在它生成的地图上 放一个预制的房间
+pub struct PrefabBuilder {
+    parent: Box<dyn MapBuilder>,
+    map: Map,
+    depth: i32,
+    history: MapHistory,
+    // 放置的房间 和 左上角的位置
+    vault: Option<(&'static PrefabVault, i32, i32)>,
+}
+
+impl MapBuilder for PrefabBuilder {
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.parent.get_starting_position()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.snapshots.clone()
+    }
+
+    fn build_map(&mut self) {
+        // 被包装的生成器 使用同样的可视化设置
+        self.parent.history().enabled = self.history.enabled;
+        self.parent.build_map();
+        self.map = self.parent.get_map();
+        self.history.snapshots = self.parent.get_snapshot_history();
+        self.place_vault();
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        self.parent.spawn_entities(ecs);
+
+        let (vault, x, y) = match self.vault {
+            Some(vault) => vault,
+            None => return,
+        };
+
+        // 删除原来的生成器 放在房间范围内的实体
+        let to_delete: Vec<Entity> = {
+            let entities = ecs.entities();
+            let positions = ecs.read_storage::<Position>();
+            let players = ecs.read_storage::<Player>();
+            (&entities, &positions, !&players)
+                .join()
+                .filter(|(_, pos, _)| {
+                    pos.x >= x
+                        && pos.x < x + vault.width()
+                        && pos.y >= y
+                        && pos.y < y + vault.height()
+                })
+                .map(|(entity, _, _)| entity)
+                .collect()
+        };
+        for entity in to_delete {
+            ecs.delete_entity(entity).expect("Unable to delete entity");
+        }
+
+        for (ty, row) in vault.template.iter().enumerate() {
+            for (tx, ch) in row.chars().enumerate() {
+                let (ex, ey) = (x + tx as i32, y + ty as i32);
+                match ch {
+                    'g' => spawner::spawn_named(ecs, ex, ey, "Goblin"),
+                    'o' => spawner::spawn_named(ecs, ex, ey, "Orc"),
+                    '!' => spawner::spawn_named(ecs, ex, ey, "Health Potion"),
+                    '$' => spawner::spawn_named(ecs, ex, ey, "Gold"),
+                    'S' => spawner::spawn_named(ecs, ex, ey, "Shopkeeper"),
+                    _ => {}
+                }
+            }
+        }
+
+        for (trigger, target, action) in vault.mechanisms.iter() {
+            let targets = vault.positions_of(*target, x, y);
+            for pos in vault.positions_of(*trigger, x, y) {
+                let mechanism = Mechanism {
+                    action: *action,
+                    targets: targets.clone(),
+                };
+                match trigger {
+                    'L' => spawner::lever(ecs, pos.x, pos.y, mechanism),
+                    // 水淹 和 放出怪物 只会发生一次
+                    _ => spawner::pressure_plate(
+                        ecs,
+                        pos.x,
+                        pos.y,
+                        mechanism,
+                        *action != MechanismAction::ToggleDoors,
+                    ),
+                }
+            }
+        }
+    }
+
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
+    }
+}
+
+impl PrefabBuilder {
+    pub fn vaults(new_depth: i32, parent: Box<dyn MapBuilder>) -> PrefabBuilder {
+        PrefabBuilder {
+            parent,
+            map: Map::new(new_depth),
+            depth: new_depth,
+            history: MapHistory::default(),
+            vault: None,
+        }
+    }
+
+    fn place_vault(&mut self) {
+        let mut rng = RandomNumberGenerator::new();
+        // 不是每一层都有预制的房间
+        if rng.roll_dice(1, 2) == 1 {
+            return;
+        }
+        let candidates: Vec<&'static PrefabVault> = VAULTS
+            .iter()
+            .filter(|v| v.first_depth <= self.depth)
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let vault = candidates[rng.roll_dice(1, candidates.len() as i32) as usize - 1];
+
+        // 找一块足够大的空地，不能盖住玩家的起点
+        let start = self.parent.get_starting_position();
+        for _ in 0..100 {
+            let x = rng.range(1, self.map.width - vault.width() - 1);
+            let y = rng.range(1, self.map.height - vault.height() - 1);
+            if start.x >= x
+                && start.x < x + vault.width()
+                && start.y >= y
+                && start.y < y + vault.height()
+            {
+                continue;
+            }
+            if !self.is_open_area(x, y, vault.width(), vault.height()) {
+                continue;
+            }
+
+            for (ty, row) in vault.template.iter().enumerate() {
+                for (tx, ch) in row.chars().enumerate() {
+                    let idx = self.map.xy_idx(x + tx as i32, y + ty as i32);
+                    self.map.tiles[idx] = match ch {
+                        '#' | '%' => TileType::Wall,
+                        '+' | '|' => TileType::ClosedDoor,
+                        _ => TileType::Floor,
+                    };
+                }
+            }
+            self.vault = Some((vault, x, y));
+            self.take_snapshot();
+            return;
+        }
+    }
+
+    // 这片区域 全部是地面
+    fn is_open_area(&self, x: i32, y: i32, width: i32, height: i32) -> bool {
+        for ty in y..y + height {
+            for tx in x..x + width {
+                let idx = self.map.xy_idx(tx, ty);
+                if self.map.tiles[idx] != TileType::Floor {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
@@ -5,10 +5,10 @@ use crate::spawner;
 use crate::Position;
 use crate::Rect;
 use crate::TileType;
-use crate::SHOW_MAPGEN_VISUALIZER;
 
 use super::Map;
 use super::MapBuilder;
+use super::MapHistory;
 use rltk::RandomNumberGenerator;
 // trait
 use specs::prelude::*;
@@ -19,7 +19,7 @@ pub struct SimpleMapBuilder {
     starting_position: Position,
     depth: i32,
     rooms: Vec<Rect>,
-    history: Vec<Map>,
+    history: MapHistory,
 }
 impl SimpleMapBuilder {
     pub fn new(new_depth: i32) -> SimpleMapBuilder {
@@ -28,7 +28,7 @@ impl SimpleMapBuilder {
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
             rooms: Vec::new(),
-            history: Vec::new(),
+            history: MapHistory::default(),
         }
     }
     fn rooms_and_corridors(&mut self) {
@@ -103,16 +103,9 @@ impl MapBuilder for SimpleMapBuilder {
         }
     }
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                //
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
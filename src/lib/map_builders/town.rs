@@ -0,0 +1,192 @@
+use super::{spawner, Map, MapBuilder, MapHistory, Position, Rect, TileType};
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+// 城镇，第一层地图
+// 一条东西方向的大路，路两边是房子：酒馆 商店 和 民居，大路的东边尽头是地下城的入口
+
+// 大路所在的行
+const ROAD_Y: i32 = 20;
+// 大路的宽度
+const ROAD_WIDTH: i32 = 3;
+const MAX_BUILDINGS: i32 = 12;
+
+// 房子的用途
+#[derive(PartialEq, Copy, Clone)]
+enum BuildingType {
+    Tavern,
+    Shop,
+    House,
+}
+
+pub struct TownBuilder {
+    map: Map,
+    starting_position: Position,
+    history: MapHistory,
+    buildings: Vec<(Rect, BuildingType)>,
+}
+
+impl MapBuilder for TownBuilder {
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position.clone()
+    }
+
+    fn get_snapshot_history(&self) -> Vec<Map> {
+        self.history.snapshots.clone()
+    }
+
+    fn build_map(&mut self) {
+        self.build();
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        let mut rng = RandomNumberGenerator::new();
+        for (building, building_type) in self.buildings.iter() {
+            let (cx, cy) = building.center();
+            match building_type {
+                BuildingType::Tavern => {
+                    spawner::townsperson(ecs, cx, cy, "Barkeep");
+                    // 酒馆里的客人
+                    for _ in 0..rng.roll_dice(1, 3) {
+                        let x = rng.range(building.x1 + 1, building.x2);
+                        let y = rng.range(building.y1 + 1, building.y2);
+                        if (x, y) != (cx, cy) {
+                            spawner::townsperson(ecs, x, y, "Patron");
+                        }
+                    }
+                }
+                BuildingType::Shop => spawner::spawn_named(ecs, cx, cy, "Shopkeeper"),
+                BuildingType::House => {
+                    if rng.roll_dice(1, 2) == 1 {
+                        spawner::townsperson(ecs, cx, cy, "Townsperson");
+                    }
+                }
+            }
+        }
+
+        // 大路上走动的村民
+        for _ in 0..rng.roll_dice(1, 4) {
+            let x = rng.range(5, self.map.width - 6);
+            let y = rng.range(ROAD_Y, ROAD_Y + ROAD_WIDTH);
+            spawner::townsperson(ecs, x, y, "Peasant");
+        }
+    }
+
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
+    }
+}
+
+impl TownBuilder {
+    pub fn new(new_depth: i32) -> TownBuilder {
+        TownBuilder {
+            map: Map::new(new_depth),
+            starting_position: Position { x: 0, y: 0 },
+            history: MapHistory::default(),
+            buildings: Vec::new(),
+        }
+    }
+
+    fn build(&mut self) {
+        let mut rng = RandomNumberGenerator::new();
+
+        // 地图的边缘是墙，其余的地方是草地
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let idx = self.map.xy_idx(x, y);
+                self.map.tiles[idx] = TileType::Grass;
+            }
+        }
+        self.take_snapshot();
+
+        // 东西方向的大路
+        for y in ROAD_Y..ROAD_Y + ROAD_WIDTH {
+            for x in 1..self.map.width - 1 {
+                let idx = self.map.xy_idx(x, y);
+                self.map.tiles[idx] = TileType::Road;
+            }
+        }
+        self.take_snapshot();
+
+        // 在大路的两边 放置不重叠的房子
+        let mut attempts = 0;
+        while self.buildings.len() < MAX_BUILDINGS as usize && attempts < 200 {
+            attempts += 1;
+            let w = rng.range(7, 13);
+            let h = rng.range(5, 9);
+            let x = rng.range(2, self.map.width - w - 2);
+            // 北边的房子 离大路留出两格，南边的一样
+            let north = rng.roll_dice(1, 2) == 1;
+            let y = if north {
+                ROAD_Y - h - 2 - rng.range(0, ROAD_Y - h - 3)
+            } else {
+                ROAD_Y
+                    + ROAD_WIDTH
+                    + 2
+                    + rng.range(0, self.map.height - ROAD_Y - ROAD_WIDTH - h - 4)
+            };
+            let building = Rect::new(x, y, w, h);
+            // 路的每一边只有一排房子，房子之间至少隔一格，这样门口的小路不会穿过别的房子
+            let overlaps = self.buildings.iter().any(|(b, _)| {
+                (b.y2 < ROAD_Y) == north && b.x1 <= building.x2 + 1 && b.x2 + 1 >= building.x1
+            });
+            if overlaps {
+                continue;
+            }
+
+            self.add_building(&building, north);
+            let building_type = match self.buildings.len() {
+                0 => BuildingType::Tavern,
+                1 | 2 => BuildingType::Shop,
+                _ => BuildingType::House,
+            };
+            self.buildings.push((building, building_type));
+            self.take_snapshot();
+        }
+
+        // 玩家从大路的西边出发，大路的东边尽头是地下城的入口
+        self.starting_position = Position {
+            x: 2,
+            y: ROAD_Y + ROAD_WIDTH / 2,
+        };
+        let stairs_idx = self.map.xy_idx(self.map.width - 3, ROAD_Y + ROAD_WIDTH / 2);
+        self.map.tiles[stairs_idx] = TileType::DownStairs;
+        self.take_snapshot();
+    }
+
+    // 墙围起来的木地板房子，朝着大路开一个门，门口有一条小路通到大路上
+    fn add_building(&mut self, building: &Rect, north: bool) {
+        for y in building.y1..=building.y2 {
+            for x in building.x1..=building.x2 {
+                let idx = self.map.xy_idx(x, y);
+                let edge =
+                    x == building.x1 || x == building.x2 || y == building.y1 || y == building.y2;
+                self.map.tiles[idx] = if edge {
+                    TileType::Wall
+                } else {
+                    TileType::WoodFloor
+                };
+            }
+        }
+
+        let (door_x, _) = building.center();
+        let (door_y, road_range) = if north {
+            (building.y2, building.y2..ROAD_Y)
+        } else {
+            (building.y1, ROAD_Y + ROAD_WIDTH..building.y1)
+        };
+        let door_idx = self.map.xy_idx(door_x, door_y);
+        self.map.tiles[door_idx] = TileType::WoodFloor;
+        for y in road_range {
+            if y == door_y {
+                continue;
+            }
+            let idx = self.map.xy_idx(door_x, y);
+            self.map.tiles[idx] = TileType::Road;
+        }
+    }
+}
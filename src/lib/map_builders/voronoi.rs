@@ -1,5 +1,5 @@
-use super::{MapBuilder, Map,
-    TileType, Position, spawner, SHOW_MAPGEN_VISUALIZER,
+use super::{MapBuilder, Map, MapHistory,
+    TileType, Position, spawner,
     remove_unreachable_areas_returning_most_distant, generate_voronoi_spawn_regions};
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -13,7 +13,7 @@ pub struct VoronoiCellBuilder {
     map : Map,
     starting_position : Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas : HashMap<i32, Vec<usize>>,
     // 种子的数量
     n_seeds: usize,
@@ -30,7 +30,7 @@ impl MapBuilder for VoronoiCellBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self)  {
@@ -43,14 +43,8 @@ impl MapBuilder for VoronoiCellBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 
@@ -61,7 +55,7 @@ impl VoronoiCellBuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             n_seeds: 64,
             distance_algorithm: DistanceAlgorithm::Pythagoras
@@ -73,7 +67,7 @@ impl VoronoiCellBuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             n_seeds: 64,
             distance_algorithm: DistanceAlgorithm::Pythagoras
@@ -85,7 +79,7 @@ impl VoronoiCellBuilder {
             map : Map::new(new_depth),
             starting_position : Position{ x: 0, y : 0 },
             depth : new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas : HashMap::new(),
             n_seeds: 64,
             distance_algorithm: DistanceAlgorithm::Manhattan
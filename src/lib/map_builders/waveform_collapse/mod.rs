@@ -1,6 +1,6 @@
 use super::{
-    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map,
-    MapBuilder, Position, TileType, SHOW_MAPGEN_VISUALIZER,
+    generate_voronoi_spawn_regions, remove_unreachable_areas_returning_most_distant, spawner, Map, MapHistory,
+    MapBuilder, Position, TileType,
 };
 use rltk::RandomNumberGenerator;
 use specs::prelude::*;
@@ -12,7 +12,7 @@ pub struct WaveformCollapseBuilder {
     map: Map,
     starting_position: Position,
     depth: i32,
-    history: Vec<Map>,
+    history: MapHistory,
     noise_areas: HashMap<i32, Vec<usize>>,
 }
 
@@ -26,7 +26,7 @@ impl MapBuilder for WaveformCollapseBuilder {
     }
 
     fn get_snapshot_history(&self) -> Vec<Map> {
-        self.history.clone()
+        self.history.snapshots.clone()
     }
 
     fn build_map(&mut self) {
@@ -39,14 +39,8 @@ impl MapBuilder for WaveformCollapseBuilder {
         }
     }
 
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() {
-                *v = true;
-            }
-            self.history.push(snapshot);
-        }
+    fn history(&mut self) -> &mut MapHistory {
+        &mut self.history
     }
 }
 
@@ -56,7 +50,7 @@ impl WaveformCollapseBuilder {
             map: Map::new(new_depth),
             starting_position: Position { x: 0, y: 0 },
             depth: new_depth,
-            history: Vec::new(),
+            history: MapHistory::default(),
             noise_areas: HashMap::new(),
         }
     }
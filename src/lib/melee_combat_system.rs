@@ -1,29 +1,38 @@
-use crate::{HungerClock, HungerState};
+use crate::{
+    Burden, ConfuseOnHit, DamageType, EffectQueue, EffectType, ElementalDamage, EquipmentSlot,
+    HungerClock, HungerState, LifeSteal, Renderable, Targets, ENCUMBRANCE_PENALTY,
+};
 
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, CombatStats, DefenseBonus, Equipped,
-    MeleePowerBonus, Name, Position, SufferDamage, WantsToMelee,
+    gamelog::GameLog, CombatStats, DefenseBonus, Equipped, MeleePowerBonus, Name, WantsToMelee,
 };
+use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 // 该系统 来 处理近战
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
-        WriteStorage<'a, SufferDamage>,
         // 装备的战斗攻击和防御加成
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
-        WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, Position>,
         ReadStorage<'a, HungerClock>,
+        WriteExpect<'a, EffectQueue>,
+        ReadStorage<'a, Burden>,
+        // 装备词缀的命中效果
+        ReadStorage<'a, ElementalDamage>,
+        ReadStorage<'a, LifeSteal>,
+        ReadStorage<'a, ConfuseOnHit>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Renderable>,
     );
     fn run(&mut self, data: Self::SystemData) {
         // destruct data 结构 data
@@ -33,83 +42,61 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut wants_melee,
             names,
             combat_stats,
-            mut inflict_damage,
             melee_power_bonuses,
             defense_bonuses,
             equipped,
-            mut particle_builder,
-            positions,
             hunger_clock,
+            mut effects,
+            burdens,
+            elemental_damages,
+            life_steals,
+            confuse_on_hits,
+            mut rng,
+            renderables,
         ) = data;
 
-        for (entity, wants_melee, name, stats) in
+        let hits = HitResolver {
+            ranged: false,
+            names: &names,
+            renderables: &renderables,
+            equipped: &equipped,
+            elemental_damages: &elemental_damages,
+            life_steals: &life_steals,
+            confuse_on_hits: &confuse_on_hits,
+        };
+        for (entity, wants_melee, _name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             // Once we've determined that the attacker is alive, we set offensive_bonus to 0. offensive 攻击
             if stats.hp > 0 {
-                let mut offensive_bonus = 0;
-                // we iterate all entities that have a MeleePowerBonus and Equipped entry, if they are equipped by attacker, we add their power bonus to offensive_bonus
-                for (_item_entity, power_bonus, equipped_by) in
-                    (&entities, &melee_power_bonuses, &equipped).join()
-                {
-                    if equipped_by.owner == entity {
-                        offensive_bonus += power_bonus.power;
-                    }
-                }
-
-                // give you a temporary + 1 to your power when you are fed
-                let hc = hunger_clock.get(entity);
-                if let Some(hc) = hc {
-                    if hc.state == HungerState::WellFed {
-                        offensive_bonus += 1;
-                    }
-                }
+                let offensive_bonus = attack_bonus(
+                    entity,
+                    false,
+                    &melee_power_bonuses,
+                    &equipped,
+                    &hunger_clock,
+                    &burdens,
+                );
 
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 // Once we have determined that the defender is alive, we set defensive_bonus to 0.
                 if target_stats.hp > 0 {
-                    let target_name = names.get(wants_melee.target).unwrap();
-
-                    let mut defensive_bonus = 0;
-                    // We iterate all entities that have a DefenseBonus and an Equipped entry. If they are equipped by the target, we add their defense to the defense_bonus.
-                    for (_item_entity, defense_bonus, equipped_by) in
-                        (&entities, &defense_bonuses, &equipped).join()
-                    {
-                        if equipped_by.owner == wants_melee.target {
-                            defensive_bonus += defense_bonus.defense;
-                        }
-                    }
-                    let pos = positions.get(wants_melee.target);
-                    //
-                    if let Some(pos) = pos {
-                        particle_builder.request(
-                            pos.x,
-                            pos.y,
-                            rltk::RGB::named(rltk::ORANGE),
-                            rltk::RGB::named(rltk::BLACK),
-                            rltk::to_cp437('‼'),
-                            200.0,
-                        );
-                    }
-
+                    let defensive_bonus =
+                        defense_bonus(wants_melee.target, &defense_bonuses, &equipped, &burdens);
                     // When we calculate damage, we add the offense bonus to the power side - and add the defense bonus to the defense side.
                     let damage = i32::max(
                         0,
                         (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
                     );
 
-                    if damage == 0 {
-                        log.entries.push(format!(
-                            "{} is unable to hurt {}",
-                            &name.name, &target_name.name
-                        ));
-                    } else {
-                        log.entries.push(format!(
-                            "{} hits {}, for {} hp.",
-                            &name.name, &target_name.name, damage
-                        ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
-                    }
+                    hits.hit(
+                        entity,
+                        wants_melee.target,
+                        damage,
+                        &mut log,
+                        &mut effects,
+                        &mut rng,
+                    );
                 }
             }
         }
@@ -117,3 +104,182 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+// 日志中 实体名字的颜色，和地图上显示的颜色一样
+pub fn name_color(renderables: &ReadStorage<Renderable>, entity: Entity) -> RGB {
+    renderables
+        .get(entity)
+        .map_or(RGB::named(rltk::WHITE), |r| r.fg)
+}
+
+pub fn damage_type_name(damage_type: DamageType) -> &'static str {
+    match damage_type {
+        DamageType::Fire => "fire",
+        DamageType::Frost => "frost",
+        DamageType::Poison => "poison",
+    }
+}
+
+// 命中目标时 用到的名字、颜色 和 装备词缀，近战 和 远程攻击 共用
+pub struct HitResolver<'s, 'a> {
+    pub ranged: bool,
+    pub names: &'s ReadStorage<'a, Name>,
+    pub renderables: &'s ReadStorage<'a, Renderable>,
+    pub equipped: &'s ReadStorage<'a, Equipped>,
+    pub elemental_damages: &'s ReadStorage<'a, ElementalDamage>,
+    pub life_steals: &'s ReadStorage<'a, LifeSteal>,
+    pub confuse_on_hits: &'s ReadStorage<'a, ConfuseOnHit>,
+}
+
+impl HitResolver<'_, '_> {
+    // damage 是扣除防御之后的伤害，加上元素伤害后 记录日志 并交给效果系统
+    // 和攻击加成一样，远程攻击 不算近战武器的词缀，近战 也不算远程武器的
+    pub fn hit(
+        &self,
+        attacker: Entity,
+        target: Entity,
+        damage: i32,
+        log: &mut GameLog,
+        effects: &mut EffectQueue,
+        rng: &mut RandomNumberGenerator,
+    ) {
+        let other_weapon = if self.ranged {
+            EquipmentSlot::Melee
+        } else {
+            EquipmentSlot::Ranged
+        };
+        let worn_by_attacker = |e: &Equipped| e.owner == attacker && e.slot != other_weapon;
+
+        // 附魔武器的元素伤害 不受防御的影响
+        let elemental: Vec<&ElementalDamage> = (self.elemental_damages, self.equipped)
+            .join()
+            .filter(|(_, equipped_by)| worn_by_attacker(equipped_by))
+            .map(|(elemental_damage, _)| elemental_damage)
+            .collect();
+        let damage = damage + elemental.iter().map(|e| e.damage).sum::<i32>();
+
+        // 名字显示为 实体的颜色
+        let attacker_name = &self.names.get(attacker).unwrap().name;
+        let target_name = &self.names.get(target).unwrap().name;
+        let attacker_color = name_color(self.renderables, attacker);
+        let target_color = name_color(self.renderables, target);
+        if damage == 0 {
+            log.entry()
+                .colored(attacker_name, attacker_color)
+                .text(" is unable to hurt ")
+                .colored(target_name, target_color)
+                .minor()
+                .log();
+            return;
+        }
+
+        let extra: Vec<String> = elemental
+            .iter()
+            .map(|e| format!("{} {}", e.damage, damage_type_name(e.damage_type)))
+            .collect();
+        let mut entry = log
+            .entry()
+            .colored(attacker_name, attacker_color)
+            .text(if self.ranged { " shoots " } else { " hits " })
+            .colored(target_name, target_color)
+            .text(", for ")
+            .damage(damage)
+            .text(" hp");
+        if !extra.is_empty() {
+            entry = entry.text(format!(" ({})", extra.join(", ")));
+        }
+        entry.text(".").log();
+        // 伤害 和 受击的粒子 由 效果系统 处理
+        effects.add_effect(
+            Some(attacker),
+            EffectType::Damage { amount: damage },
+            Targets::Single { target },
+        );
+
+        // 命中时 装备词缀的效果
+        for (life_steal, equipped_by) in (self.life_steals, self.equipped).join() {
+            if worn_by_attacker(equipped_by) {
+                effects.add_effect(
+                    Some(attacker),
+                    EffectType::Healing {
+                        amount: life_steal.amount,
+                    },
+                    Targets::Single { target: attacker },
+                );
+            }
+        }
+        for (confuse, equipped_by) in (self.confuse_on_hits, self.equipped).join() {
+            if worn_by_attacker(equipped_by) && rng.roll_dice(1, 4) == 1 {
+                log.entry()
+                    .colored(target_name, target_color)
+                    .text(" is confused!")
+                    .log();
+                effects.add_effect(
+                    Some(attacker),
+                    EffectType::Confusion {
+                        turns: confuse.turns,
+                    },
+                    Targets::Single { target },
+                );
+            }
+        }
+    }
+}
+
+// 攻击加成：装备、吃饱了 和 负重，近战 和 远程攻击 共用
+// 远程攻击 不算近战武器的加成，近战 也不算远程武器的
+pub fn attack_bonus(
+    attacker: Entity,
+    ranged: bool,
+    power_bonuses: &ReadStorage<MeleePowerBonus>,
+    equipped: &ReadStorage<Equipped>,
+    hunger_clock: &ReadStorage<HungerClock>,
+    burdens: &ReadStorage<Burden>,
+) -> i32 {
+    let mut offensive_bonus = 0;
+    // we iterate all entities that have a MeleePowerBonus and Equipped entry, if they are equipped by attacker, we add their power bonus to offensive_bonus
+    let other_weapon = if ranged {
+        EquipmentSlot::Melee
+    } else {
+        EquipmentSlot::Ranged
+    };
+    for (power_bonus, equipped_by) in (power_bonuses, equipped).join() {
+        if equipped_by.owner == attacker && equipped_by.slot != other_weapon {
+            offensive_bonus += power_bonus.power;
+        }
+    }
+
+    // give you a temporary + 1 to your power when you are fed
+    if let Some(hc) = hunger_clock.get(attacker) {
+        if hc.state == HungerState::WellFed {
+            offensive_bonus += 1;
+        }
+    }
+
+    // 负重过多，攻击变弱
+    if burdens.get(attacker).is_some_and(|b| b.is_encumbered()) {
+        offensive_bonus -= ENCUMBRANCE_PENALTY;
+    }
+    offensive_bonus
+}
+
+// 防御加成：装备 和 负重
+pub fn defense_bonus(
+    defender: Entity,
+    defense_bonuses: &ReadStorage<DefenseBonus>,
+    equipped: &ReadStorage<Equipped>,
+    burdens: &ReadStorage<Burden>,
+) -> i32 {
+    let mut defensive_bonus = 0;
+    // We iterate all entities that have a DefenseBonus and an Equipped entry. If they are equipped by the target, we add their defense to the defense_bonus.
+    for (defense_bonus, equipped_by) in (defense_bonuses, equipped).join() {
+        if equipped_by.owner == defender {
+            defensive_bonus += defense_bonus.defense;
+        }
+    }
+    // 负重过多，防御变弱
+    if burdens.get(defender).is_some_and(|b| b.is_encumbered()) {
+        defensive_bonus -= ENCUMBRANCE_PENALTY;
+    }
+    defensive_bonus
+}
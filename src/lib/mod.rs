@@ -34,7 +34,6 @@ mod melee_combat_system;
 use melee_combat_system::*;
 
 mod damage_system;
-use damage_system::*;
 
 pub mod gui;
 pub use gui::*;
@@ -70,6 +69,70 @@ pub use rex_assets::*;
 // 陷阱触发系统
 pub mod trigger_system;
 pub use trigger_system::*;
+
+// 效果系统，物品 陷阱 攻击 产生的效果在这里统一处理
+pub mod effects;
+pub use effects::*;
+
+// 物品鉴定
+pub mod identification;
+pub use identification::*;
+
+// 物品堆叠
+pub mod stacking;
+pub use stacking::*;
+
+// 负重系统
+pub mod encumbrance_system;
+pub use encumbrance_system::*;
+
+// 金币 和 商店
+pub mod shop;
+pub use shop::*;
+
+// 物品合成
+pub mod crafting;
+pub use crafting::*;
+
+// 装备的随机词缀
+pub mod affixes;
+pub use affixes::*;
+
+// 施法系统
+pub mod spell_system;
+pub use spell_system::*;
+
+// 中毒系统
+pub mod poison_system;
+pub use poison_system::*;
+
+// 搜索 和 解除陷阱
+pub mod traps;
+pub use traps::*;
+
+// 投射物的飞行路线
+pub mod projectile;
+pub use projectile::*;
+
+// 弓弩的射击
+pub mod ranged_combat_system;
+pub use ranged_combat_system::*;
+
+// 投掷物品
+pub mod throw_system;
+pub use throw_system::*;
+
+// 按键绑定
+pub mod keymap;
+pub use keymap::*;
+
+// 自动寻路
+pub mod travel;
+pub use travel::*;
+
+// 游戏的设置
+pub mod settings;
+pub use settings::*;
 // ------------------------World state section------------------------
 // turn-base game,回合制游戏，game state
 //Copy 将其标记为“复制”类型 - 它可以安全地复制到内存中（意味着它没有会被搞乱的指针）。 Clone 悄悄地为其添加了 .clone() 功能，允许您以这种方式进行内存复制。
@@ -86,15 +149,58 @@ pub enum RunState {
     ShowDropItem,
     // 可移除装备的列表
     ShowRemoveItem,
+    // 鉴定物品的菜单，scroll 是正在使用的鉴定卷轴
+    ShowIdentify {
+        scroll: Entity,
+    },
+    // 装备栏
+    ShowEquipment,
+    // 角色面板
+    ShowCharacter,
+    // 帮助界面，列出当前的按键绑定
+    ShowHelp,
+    // 沿着自动寻路的路径 一步一步地走
+    Travelling,
+    // 合成物品的菜单
+    ShowCrafting,
+    // 选择要施放的法术
+    ShowSpells,
+    // 选择要扔出去的物品
+    ShowThrowItem,
+    // 全屏的日志，offset 是往前翻过的条数
+    ShowLog {
+        offset: i32,
+    },
+    // 观察模式，用键盘移动光标 查看地图上的东西
+    LookMode {
+        cursor: Point,
+    },
+    // 和商人交易
+    ShowVendor {
+        vendor: Entity,
+        mode: gui::VendorMode,
+    },
     // 显示攻击目标
     ShowTargeting {
         range: i32,
         item: Entity,
     },
+    // 用远程武器瞄准
+    ShowFiring {
+        target: Entity,
+    },
+    // 选择物品扔向哪里
+    ShowThrowTarget {
+        item: Entity,
+    },
     // 处于菜单，存储当前的选项, gui 绘制菜单
     MainMenu {
         menu_selection: gui::MainMenuSelection,
     },
+    // 主菜单中的选项界面
+    OptionsMenu {
+        selection: gui::OptionsSelection,
+    },
     // 保存游戏的状态
     SaveGame,
     NextLevel,
@@ -122,9 +228,6 @@ pub struct State {
     pub mapgen_timer: f32,
 }
 
-// 是否显示可视化地图加载
-const SHOW_MAPGEN_VISUALIZER: bool = true;
-
 impl State {
     // 系统的调度
     fn run_systems(&mut self) {
@@ -145,10 +248,10 @@ impl State {
         // 战斗系统
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
-
-        // 攻击伤害系统
-        let mut damage = DamageSystem {};
-        damage.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+        let mut throw = ThrowSystem {};
+        throw.run_now(&self.ecs);
 
         // 物品拾取系统
         let mut pickup = ItemCollectionSystem {};
@@ -158,6 +261,10 @@ impl State {
         let mut potions = ItemUseSystem {};
         potions.run_now(&self.ecs);
 
+        // 施法系统
+        let mut spells = SpellCastSystem {};
+        spells.run_now(&self.ecs);
+
         // 物品 丢弃系统
         let mut drop_items = ItemDropSystem {};
         drop_items.run_now(&self.ecs);
@@ -166,10 +273,21 @@ impl State {
         let mut item_remove = ItemRemoveSystem {};
         item_remove.run_now(&self.ecs);
 
+        // 负重系统
+        let mut encumbrance = EncumbranceSystem {};
+        encumbrance.run_now(&self.ecs);
+
         // 饥饿时钟系统
         let mut hunger = hunger_system::HungerSystem {};
         hunger.run_now(&self.ecs);
 
+        // 中毒系统
+        let mut poison = PoisonSystem {};
+        poison.run_now(&self.ecs);
+
+        // 处理上面各个系统放进队列中的效果
+        effects::run_effects_queue(&mut self.ecs);
+
         // 粒子系统
         let mut particles = particle_system::ParticleSpawnSystem {};
         particles.run_now(&self.ecs);
@@ -186,6 +304,7 @@ impl State {
         let backpack = self.ecs.read_storage::<InBackpack>();
         let player_entity = self.ecs.fetch::<Entity>();
         let equipped = self.ecs.read_storage::<Equipped>();
+        let known_spells = self.ecs.read_storage::<KnownSpell>();
 
         // 待删除的列表
         let mut to_delete: Vec<Entity> = Vec::new();
@@ -211,6 +330,12 @@ impl State {
                     should_delete = false;
                 }
             }
+            // 玩家学会的法术
+            if let Some(spell) = known_spells.get(entity) {
+                if spell.owner == *player_entity {
+                    should_delete = false;
+                }
+            }
             if should_delete {
                 to_delete.push(entity);
             }
@@ -239,9 +364,7 @@ impl State {
         // Notify the player and give them some health
         let player_entity = self.ecs.fetch::<Entity>();
         let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-        gamelog
-            .entries
-            .push("You descend to the next level, and take a moment to heal.".to_string());
+        gamelog.push("You descend to the next level, and take a moment to heal.");
         let mut player_health_store = self.ecs.write_storage::<CombatStats>();
         let player_health = player_health_store.get_mut(*player_entity);
         if let Some(player_health) = player_health {
@@ -266,6 +389,12 @@ impl State {
             *player_entity_writer = player_entity;
         }
 
+        // 新的一局游戏，物品的外观名字重新随机
+        {
+            let mut registry = self.ecs.write_resource::<ItemRegistry>();
+            *registry = ItemRegistry::new();
+        }
+
         // Build a new map and place the player
         self.generate_world_map(1);
     }
@@ -275,7 +404,8 @@ impl State {
         self.mapgen_index = 0;
         self.mapgen_timer = 0.0;
         self.mapgen_history.clear();
-        let mut builder = map_builders::random_builder(new_depth);
+        let show_mapgen_visualizer = self.ecs.fetch::<Settings>().show_mapgen_visualizer;
+        let mut builder = map_builders::random_builder(new_depth, show_mapgen_visualizer);
         builder.build_map();
         self.mapgen_history = builder.get_snapshot_history();
         let player_start;
@@ -331,6 +461,7 @@ impl GameState for State {
         match newrunstate {
             // handle the mainmenu state in our large match, 处理 处于菜单的状态
             RunState::MainMenu { .. } => {}
+            RunState::OptionsMenu { .. } => {}
             RunState::GameOver { .. } => {}
             _ => {
                 // --------------------render ---------------------------------------------------
@@ -372,7 +503,7 @@ impl GameState for State {
         match newrunstate {
             RunState::MapGeneration => {
                 // 如何没有开启地图的可视化加载，直接转到游戏的下一个运行状态
-                if !SHOW_MAPGEN_VISUALIZER {
+                if !self.ecs.fetch::<Settings>().show_mapgen_visualizer {
                     newrunstate = self.mapgen_next_state.unwrap();
                 }
 
@@ -409,13 +540,34 @@ impl GameState for State {
                     RunState::MagicMapReveal { .. } => {
                         newrunstate = RunState::MagicMapReveal { row: 0 }
                     }
+                    RunState::ShowIdentify { scroll } => {
+                        newrunstate = RunState::ShowIdentify { scroll }
+                    }
+                    // 掉进了陷坑
+                    RunState::NextLevel => newrunstate = RunState::NextLevel,
                     _ => newrunstate = RunState::MonsterTurn,
                 }
             }
             RunState::MonsterTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
+                // 怪物行动之后 一个回合结束
+                self.ecs.fetch_mut::<gamelog::GameLog>().turn += 1;
+                // 自动寻路时 接着走下一步
+                if self.ecs.fetch::<Travel>().is_active() {
+                    newrunstate = RunState::Travelling;
+                } else {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            // 按任意键 或者 点击鼠标 停下来
+            RunState::Travelling => {
+                if ctx.key.is_some() || ctx.left_click {
+                    self.ecs.fetch_mut::<Travel>().stop();
+                    newrunstate = RunState::AwaitingInput;
+                } else {
+                    newrunstate = travel_step(&mut self.ecs);
+                }
             }
             RunState::ShowInventory => {
                 // 如果在库存状态下按下 Cancel 退出 ShowInventory 状态
@@ -432,7 +584,7 @@ impl GameState for State {
                         let is_item_ranged = is_ranged.get(item_entity);
                         let names = self.ecs.read_storage::<Name>();
                         let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-                        gamelog.entries.push(format!(
+                        gamelog.push(format!(
                             "You try to use {}, but it isn't written yet",
                             names.get(item_entity).unwrap().name
                         ));
@@ -470,7 +622,8 @@ impl GameState for State {
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
                         // 3 丢弃选中的物品, 得到选中的物品
-                        let item_entity = result.1.unwrap();
+                        // 成堆的物品 每次只丢弃一个
+                        let item_entity = split_stack(&mut self.ecs, result.1.unwrap());
                         // 得到 WantsToDropItem 的 存储组件
                         let mut intent = self.ecs.write_storage::<WantsToDropItem>();
                         // 在这个存储组件插入 这个 选中的实体
@@ -504,29 +657,277 @@ impl GameState for State {
                     }
                 }
             }
+            // 装备栏 只是查看，不消耗回合
+            RunState::ShowEquipment => {
+                if gui::show_equipment(self, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowCharacter => {
+                if gui::show_character(self, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowHelp => {
+                if gui::show_help(self, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            // 合成物品 消耗一个回合
+            RunState::ShowCrafting => {
+                let result = gui::crafting_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let recipe = &RECIPES[result.1.unwrap()];
+                        if craft(&mut self.ecs, recipe) {
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowSpells => {
+                let result = gui::show_spells(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let spell = result.1.unwrap();
+                        // 远程法术 和 远程物品一样 先选择目标
+                        let range = self.ecs.read_storage::<Ranged>().get(spell).map(|r| r.range);
+                        if let Some(range) = range {
+                            newrunstate = RunState::ShowTargeting { range, item: spell };
+                        } else {
+                            self.ecs
+                                .write_storage::<WantsToCastSpell>()
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToCastSpell {
+                                        spell,
+                                        target: None,
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            // 和商人交易，买卖不消耗回合
+            RunState::ShowVendor { vendor, mode } => {
+                let result = gui::show_vendor(self, ctx, vendor, mode);
+                match result.0 {
+                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::VendorResult::NoResponse => {}
+                    gui::VendorResult::SwitchMode => {
+                        let mode = match mode {
+                            gui::VendorMode::Buy => gui::VendorMode::Sell,
+                            gui::VendorMode::Sell => gui::VendorMode::Buy,
+                        };
+                        newrunstate = RunState::ShowVendor { vendor, mode };
+                    }
+                    gui::VendorResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        match mode {
+                            gui::VendorMode::Buy => buy_item(&mut self.ecs, item_entity),
+                            gui::VendorMode::Sell => sell_item(&mut self.ecs, vendor, item_entity),
+                        }
+                    }
+                }
+            }
+            // 鉴定之后 才消耗鉴定卷轴 并轮到怪物行动，取消时 卷轴还在
+            RunState::ShowIdentify { scroll } => {
+                let result = gui::identify_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        identify_item(&mut self.ecs, scroll, result.1.unwrap());
+                        newrunstate = RunState::MonsterTurn;
+                    }
+                }
+            }
             // 在 显示攻击选择菜单
             RunState::ShowTargeting { range, item } => {
-                let result = gui::ranged_target(self, ctx, range);
+                let radius = self
+                    .ecs
+                    .read_storage::<AreaOfEffect>()
+                    .get(item)
+                    .map(|aoe| aoe.radius);
+                let result = gui::ranged_target(self, ctx, range, radius);
                 // 根据选项菜单的结果进行匹配
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        // 将攻击目标放入 WantsToUseItem 意图组件存储器中
-                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                        intent
-                            .insert(
-                                *self.ecs.fetch::<Entity>(),
-                                WantsToUseItem {
-                                    item,
-                                    target: result.1,
-                                },
-                            )
-                            .expect("Unable to insert intent");
-                        newrunstate = RunState::PlayerTurn;
+                        // 投射物沿直线飞行，被挡住时 落在挡住它的地方
+                        let player_pos = *self.ecs.fetch::<Point>();
+                        let path = projectile_path(&self.ecs, player_pos, result.1.unwrap());
+                        // 紧挨着的就是墙，什么都打不到，重新选择
+                        match path.last().copied() {
+                            None => {
+                                self.ecs
+                                    .fetch_mut::<gamelog::GameLog>()
+                                    .push("Something is in the way.");
+                                newrunstate = RunState::AwaitingInput;
+                            }
+                            Some(target) => {
+                                animate_projectile(
+                                    &self.ecs,
+                                    &path,
+                                    rltk::to_cp437('*'),
+                                    rltk::RGB::named(rltk::CYAN),
+                                );
+                                // 施放法术 或者 使用物品
+                                if self.ecs.read_storage::<Spell>().get(item).is_some() {
+                                    self.ecs
+                                        .write_storage::<WantsToCastSpell>()
+                                        .insert(
+                                            *self.ecs.fetch::<Entity>(),
+                                            WantsToCastSpell {
+                                                spell: item,
+                                                target: Some(target),
+                                            },
+                                        )
+                                        .expect("Unable to insert intent");
+                                } else {
+                                    // 将攻击目标放入 WantsToUseItem 意图组件存储器中
+                                    let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                                    intent
+                                        .insert(
+                                            *self.ecs.fetch::<Entity>(),
+                                            WantsToUseItem {
+                                                item,
+                                                target: Some(target),
+                                            },
+                                        )
+                                        .expect("Unable to insert intent");
+                                }
+                                newrunstate = RunState::PlayerTurn;
+                            }
+                        }
                     }
                 }
             }
+            // 查看日志 不消耗回合
+            RunState::ShowLog { offset } => {
+                let result = gui::show_log(self, ctx, offset);
+                match result.0 {
+                    gui::LogViewResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::LogViewResult::NoResponse => {}
+                    gui::LogViewResult::Scrolled => newrunstate = RunState::ShowLog { offset: result.1 },
+                }
+            }
+            // 观察模式 不消耗回合
+            RunState::LookMode { cursor } => {
+                let result = gui::look_mode(self, ctx, cursor);
+                match result.0 {
+                    gui::LookResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::LookResult::NoResponse => {}
+                    gui::LookResult::Moved => newrunstate = RunState::LookMode { cursor: result.1 },
+                }
+            }
+            // 选择要扔出去的物品
+            RunState::ShowThrowItem => {
+                let result = gui::throw_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = RunState::ShowThrowTarget {
+                            item: result.1.unwrap(),
+                        };
+                    }
+                }
+            }
+            // 和远程物品一样选择目标，药水 显示摔碎后的范围
+            RunState::ShowThrowTarget { item } => {
+                let radius = self
+                    .ecs
+                    .read_storage::<Potion>()
+                    .get(item)
+                    .map(|_| SHATTER_RADIUS);
+                let result = gui::ranged_target(self, ctx, THROW_RANGE, radius);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let player_pos = *self.ecs.fetch::<Point>();
+                        let path = projectile_path(&self.ecs, player_pos, result.1.unwrap());
+                        // 紧挨着的就是墙，扔不出去
+                        match path.last().copied() {
+                            None => {
+                                self.ecs
+                                    .fetch_mut::<gamelog::GameLog>()
+                                    .push("Something is in the way.");
+                                newrunstate = RunState::AwaitingInput;
+                            }
+                            Some(target) => {
+                                let glyph = self
+                                    .ecs
+                                    .read_storage::<Renderable>()
+                                    .get(item)
+                                    .map_or(rltk::to_cp437('*'), |r| r.glyph);
+                                animate_projectile(&self.ecs, &path, glyph, rltk::RGB::named(rltk::WHITE));
+                                // 成堆的物品 每次只扔出去一个
+                                let item = split_stack(&mut self.ecs, item);
+                                self.ecs
+                                    .write_storage::<WantsToThrow>()
+                                    .insert(*self.ecs.fetch::<Entity>(), WantsToThrow { item, target })
+                                    .expect("Unable to insert intent");
+                                newrunstate = RunState::PlayerTurn;
+                            }
+                        }
+                    }
+                }
+            }
+            // 瞄准 视野中射程内的目标
+            RunState::ShowFiring { target } => match gui::fire_target(self, ctx, target) {
+                gui::FireResult::Cancel => newrunstate = RunState::AwaitingInput,
+                gui::FireResult::NoResponse => {}
+                gui::FireResult::NextTarget => {
+                    let targets = ranged_targets(&self.ecs);
+                    if let Some(i) = targets.iter().position(|t| *t == target) {
+                        newrunstate = RunState::ShowFiring {
+                            target: targets[(i + 1) % targets.len()],
+                        };
+                    } else if let Some(first) = targets.first() {
+                        newrunstate = RunState::ShowFiring { target: *first };
+                    }
+                }
+                gui::FireResult::Fire => {
+                    let player_pos = *self.ecs.fetch::<Point>();
+                    let target_pos = {
+                        let positions = self.ecs.read_storage::<Position>();
+                        let pos = positions.get(target).unwrap();
+                        Point::new(pos.x, pos.y)
+                    };
+                    let path = projectile_path(&self.ecs, player_pos, target_pos);
+                    // 紧挨着的就是墙，箭射不出去
+                    match path.last().copied() {
+                        None => {
+                            self.ecs
+                                .fetch_mut::<gamelog::GameLog>()
+                                .push("Something is in the way.");
+                            newrunstate = RunState::AwaitingInput;
+                        }
+                        Some(target) => {
+                            animate_projectile(
+                                &self.ecs,
+                                &path,
+                                rltk::to_cp437('-'),
+                                rltk::RGB::named(rltk::BURLYWOOD),
+                            );
+                            self.ecs
+                                .write_storage::<WantsToShoot>()
+                                .insert(*self.ecs.fetch::<Entity>(), WantsToShoot { target })
+                                .expect("Unable to insert intent");
+                            newrunstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            },
             RunState::MainMenu { .. } => {
                 // 得到菜单及其选项
                 let result = gui::main_menu(self, ctx);
@@ -545,12 +946,64 @@ impl GameState for State {
                             // 重新加载后，删除游戏存档文件
                             saveload_system::delete_save();
                         }
+                        gui::MainMenuSelection::Options => {
+                            newrunstate = RunState::OptionsMenu {
+                                selection: gui::OptionsSelection::MapgenVisualizer,
+                            }
+                        }
                         gui::MainMenuSelection::Quit => {
                             ::std::process::exit(0);
                         }
                     },
                 }
             }
+            // 修改设置后 马上保存到设置文件
+            RunState::OptionsMenu { .. } => match gui::options_menu(self, ctx) {
+                gui::OptionsMenuResult::NoSelection { selected } => {
+                    newrunstate = RunState::OptionsMenu {
+                        selection: selected,
+                    }
+                }
+                gui::OptionsMenuResult::Changed { selected } => {
+                    let mut settings = self.ecs.fetch_mut::<Settings>();
+                    match selected {
+                        gui::OptionsSelection::MapgenVisualizer => {
+                            settings.show_mapgen_visualizer = !settings.show_mapgen_visualizer
+                        }
+                        gui::OptionsSelection::Scanlines => {
+                            settings.scanlines = !settings.scanlines;
+                            ctx.with_post_scanlines(settings.scanlines);
+                        }
+                        gui::OptionsSelection::Font => {
+                            settings.font = match settings.font {
+                                Font::Terminal8x8 => Font::Vga8x16,
+                                Font::Vga8x16 => Font::Terminal8x8,
+                            }
+                        }
+                        gui::OptionsSelection::Size => {
+                            settings.scale = settings.scale % MAX_SCALE + 1
+                        }
+                        gui::OptionsSelection::Verbosity => {
+                            settings.verbosity = match settings.verbosity {
+                                Verbosity::All => Verbosity::Brief,
+                                Verbosity::Brief => Verbosity::All,
+                            }
+                        }
+                        gui::OptionsSelection::Back => {}
+                    }
+                    if let Err(error) = settings.save() {
+                        eprintln!("{}", error);
+                    }
+                    newrunstate = RunState::OptionsMenu {
+                        selection: selected,
+                    };
+                }
+                gui::OptionsMenuResult::Back => {
+                    newrunstate = RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::Options,
+                    }
+                }
+            },
             // 处理 这个 状态 下的逻辑
             RunState::SaveGame => {
                 saveload_system::save_game(&mut self.ecs);
@@ -1,6 +1,6 @@
 use super::{Map, Monster, Position, Viewshed};
 use crate::*;
-use rltk::{console, field_of_view, Point};
+use rltk::{console, field_of_view, Point, RandomNumberGenerator};
 use specs::prelude::*;
 // 怪物思考的系统 System
 pub struct MonsterAI {}
@@ -22,6 +22,10 @@ impl<'a> System<'a> for MonsterAI {
         WriteStorage<'a, Confusion>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, EntryTrigger>,
+        ReadStorage<'a, Hidden>,
+        WriteExpect<'a, RandomNumberGenerator>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -38,6 +42,10 @@ impl<'a> System<'a> for MonsterAI {
             mut confused,
             mut particle_builder,
             mut entity_moved,
+            mut asleep,
+            entry_triggers,
+            hidden,
+            mut rng,
         ) = data;
         // monsterAI system 只有在 Mons特人Trun怪物游戏状态才可以运行
 
@@ -45,11 +53,24 @@ impl<'a> System<'a> for MonsterAI {
             return;
         }
 
+        // 怪物知道 已经暴露出来的陷阱
+        let known_traps: Vec<usize> = (&entry_triggers, &position, !&hidden)
+            .join()
+            .map(|(_, pos, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+
         for (entity, mut viewshed, _monster, mut pos) in
             (&entities, &mut viewshed, &monster, &mut position).join()
         {
             // 怪物 能否 行动 的标志
             let mut can_act = true;
+            // 睡着的怪物 看到玩家后 有几率醒过来
+            if asleep.get(entity).is_some() {
+                if viewshed.visible_tiles.contains(&*player_pos) && rng.roll_dice(1, 4) == 1 {
+                    asleep.remove(entity);
+                }
+                continue;
+            }
             // 得到 confused 的怪物
             let is_confused = confused.get_mut(entity);
             if let Some(i_anm_confused) = is_confused {
@@ -87,12 +108,21 @@ impl<'a> System<'a> for MonsterAI {
                 }
                 // 如果怪物看见玩家，会做出什么行为
                 else if viewshed.visible_tiles.contains(&*player_pos) {
+                    // 寻路时 把已知的陷阱当作障碍物 绕开它们
+                    let was_blocked: Vec<bool> =
+                        known_traps.iter().map(|idx| map.blocked[*idx]).collect();
+                    for idx in known_traps.iter() {
+                        map.blocked[*idx] = true;
+                    }
                     // 玩家 和 怪物之间的路径
                     let path = rltk::a_star_search(
                         map.xy_idx(pos.x, pos.y) as i32,
                         map.xy_idx(player_pos.x, player_pos.y) as i32,
                         &mut *map,
                     );
+                    for (idx, blocked) in known_traps.iter().zip(was_blocked) {
+                        map.blocked[*idx] = blocked;
+                    }
                     // 将怪物移动到该位置
                     // `steps` is a vector of each step towards the target, *including* the starting position.
                     if path.success && path.steps.len() > 1 {
@@ -1,7 +1,7 @@
 use crate::{Position, Renderable};
 
 use super::{ParticleLifetime, Rltk};
-use rltk::RGB;
+use rltk::{Point, RGB};
 use specs::prelude::*;
 // 粒子系统
 
@@ -17,12 +17,15 @@ struct ParticleRequest {
 
 pub struct ParticleBuilder {
     requests: Vec<ParticleRequest>,
+    // 延迟出现的粒子，和还要等待的时间
+    delayed: Vec<(f32, ParticleRequest)>,
 }
 
 impl ParticleBuilder {
     pub fn new() -> ParticleBuilder {
         ParticleBuilder {
             requests: Vec::new(),
+            delayed: Vec::new(),
         }
     }
 
@@ -45,6 +48,30 @@ impl ParticleBuilder {
             lifetime,
         });
     }
+
+    // 沿着路线 一格接一格地出现的粒子，用来显示飞行中的投射物
+    pub fn request_along_path(
+        &mut self,
+        path: &[Point],
+        fg: RGB,
+        bg: RGB,
+        glyph: rltk::FontCharType,
+        ms_per_step: f32,
+    ) {
+        for (i, pt) in path.iter().enumerate() {
+            self.delayed.push((
+                i as f32 * ms_per_step,
+                ParticleRequest {
+                    x: pt.x,
+                    y: pt.y,
+                    fg,
+                    bg,
+                    glyph,
+                    lifetime: ms_per_step,
+                },
+            ));
+        }
+    }
 }
 
 // 粒子在生命周期结束后消失
@@ -65,6 +92,38 @@ pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
     for dead in dead_particles.iter() {
         ecs.delete_entity(*dead).expect("Particle will not dead");
     }
+
+    // 等待时间到了的粒子 移到请求列表中
+    // 这里每一帧都会运行，不用等到下一个回合
+    let ready: Vec<ParticleRequest> = {
+        let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+        for (delay, _) in particle_builder.delayed.iter_mut() {
+            *delay -= ctx.frame_time_ms;
+        }
+        let (ready, waiting) = particle_builder
+            .delayed
+            .drain(..)
+            .partition(|(delay, _)| *delay <= 0.0);
+        particle_builder.delayed = waiting;
+        ready.into_iter().map(|(_, request)| request).collect()
+    };
+    for request in ready {
+        ecs.create_entity()
+            .with(Position {
+                x: request.x,
+                y: request.y,
+            })
+            .with(Renderable {
+                fg: request.fg,
+                bg: request.bg,
+                glyph: request.glyph,
+                render_order: 0,
+            })
+            .with(ParticleLifetime {
+                lifetime_ms: request.lifetime,
+            })
+            .build();
+    }
 }
 
 // 实际的粒子系统
@@ -1,18 +1,20 @@
 use crate::*;
 pub use map::*;
-use rltk::{Point, Rltk, VirtualKeyCode};
+use rltk::{Point, Rltk};
 use specs::prelude::*;
 
 use specs_derive::Component;
 use std::cmp::{max, min};
 
-// player move
-fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+// player move, 撞到商人时 打开交易界面
+pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     // 获得战斗状态组件的存储器
     let combat_stats = ecs.read_storage::<CombatStats>();
+    let vendors = ecs.read_storage::<Vendor>();
+    let levers = ecs.read_storage::<Lever>();
     // 攻击意图组件的存储器
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
 
@@ -33,12 +35,29 @@ fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             || pos.y + delta_y < 1
             || pos.y + delta_y > map.height - 1
         {
-            return;
+            return RunState::PlayerTurn;
         }
         // 目标位置 索引
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
         // 潜在的目标
         for potential_target in map.tile_content[destination_idx].iter() {
+            if vendors.get(*potential_target).is_some() {
+                return RunState::ShowVendor {
+                    vendor: *potential_target,
+                    mode: gui::VendorMode::Buy,
+                };
+            }
+            // 撞上拉杆 就拉动它
+            if levers.get(*potential_target).is_some() {
+                ecs.write_resource::<EffectQueue>().add_effect(
+                    Some(entity),
+                    EffectType::TriggerFire {
+                        trigger: *potential_target,
+                    },
+                    Targets::Single { target: entity },
+                );
+                return RunState::PlayerTurn;
+            }
             let target = combat_stats.get(*potential_target);
             if let Some(_target) = target {
                 // 给 entity 插入 WantsToMelee 组件
@@ -50,7 +69,7 @@ fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
                         },
                     )
                     .expect("Add target failed");
-                return;
+                return RunState::PlayerTurn;
             }
         }
         // 现在不会越过Wall 也不会 踩过（walking over） 怪物
@@ -72,6 +91,7 @@ fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             ppos.y = pos.y;
         }
     }
+    RunState::PlayerTurn
 }
 fn get_item(ecs: &mut World) {
     // obtains a bunch of references/accessors (访问器 和引用器) from the ECS, and iterates all items with a position
@@ -92,9 +112,7 @@ fn get_item(ecs: &mut World) {
 
     match target_item {
         // 打印日志
-        None => gamelog
-            .entries
-            .push("There is nothing here to pick up.".to_string()),
+        None => gamelog.push("There is nothing here to pick up."),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup
@@ -152,6 +170,10 @@ fn skip_turn(ecs: &mut World) -> RunState {
         let mut health_components = ecs.write_storage::<CombatStats>();
         let palyer_hp = health_components.get_mut(*player_entity).unwrap();
         palyer_hp.hp = i32::min(palyer_hp.hp + 1, palyer_hp.max_hp);
+        // 法力 和生命一样 在休息时恢复
+        if let Some(mana) = ecs.write_storage::<Mana>().get_mut(*player_entity) {
+            mana.current = i32::min(mana.current + 1, mana.max);
+        }
     }
     RunState::PlayerTurn
 }
@@ -164,57 +186,94 @@ pub fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog
-            .entries
-            .push("There is no way down from here.".to_string());
+        gamelog.push("There is no way down from here.");
         false
     }
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    // 等待输入时 不会有正在进行的行程
+    gs.ecs.fetch_mut::<Travel>().stop();
+
+    // 点击地图上的位置 自动走过去
+    if ctx.key.is_none() && ctx.left_click {
+        let (x, y) = ctx.mouse_pos();
+        return travel_to(&mut gs.ecs, Point::new(x, y));
+    }
+
     // Player movement
     if let Some(key) = ctx.key {
-        match key {
-            // 四方移动
-            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, &mut gs.ecs)
-            }
-            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, &mut gs.ecs)
+        // 按键先翻译成动作，没有绑定的按键 什么也不做
+        let action = match gs.ecs.fetch::<KeyMap>().action(key) {
+            Some(action) => action,
+            None => return RunState::AwaitingInput,
+        };
+        // 八方移动，按住 Shift 时 一直跑
+        if let Some((dx, dy)) = action.direction() {
+            if ctx.shift {
+                return start_run(&mut gs.ecs, dx, dy);
             }
+            return try_move_player(dx, dy, &mut gs.ecs);
+        }
+        match action {
+            // 拾取 物品
+            Action::PickUp => get_item(&mut gs.ecs),
+            // 显示库存
+            Action::Inventory => return RunState::ShowInventory,
+            // 显示丢弃菜单
+            Action::Drop => return RunState::ShowDropItem,
 
-            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, &mut gs.ecs)
-            }
+            // Save and Quit
+            Action::SaveAndQuit => return RunState::SaveGame,
 
-            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, &mut gs.ecs)
-            }
+            // 显示卸载装备的列表
+            Action::RemoveItem => return RunState::ShowRemoveItem,
 
-            // Diagonals 对角线移动
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => try_move_player(1, -1, &mut gs.ecs),
+            // 查看装备栏
+            Action::Equipment => return RunState::ShowEquipment,
 
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::U => try_move_player(-1, -1, &mut gs.ecs),
+            // 合成物品
+            Action::Crafting => return RunState::ShowCrafting,
 
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => try_move_player(1, 1, &mut gs.ecs),
+            // 施放法术
+            Action::CastSpell => return RunState::ShowSpells,
 
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
+            // 搜索隐藏的陷阱
+            Action::Search => return search_for_traps(&mut gs.ecs),
 
-            // G 键 拾取 物品
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            // i 键显示库存
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            // D 显示丢弃菜单
-            VirtualKeyCode::D => return RunState::ShowDropItem,
+            // 解除身边的陷阱
+            Action::Disarm => return disarm_trap(&mut gs.ecs),
 
-            // Save and Quit
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+            // 用远程武器射击
+            Action::Fire => return start_firing(&mut gs.ecs),
 
-            // 显示卸载装备的列表
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            // 投掷物品
+            Action::Throw => return RunState::ShowThrowItem,
+
+            // 查看全部的日志
+            Action::MessageLog => return RunState::ShowLog { offset: 0 },
+
+            // 角色面板
+            Action::Character => return RunState::ShowCharacter,
+
+            // 列出按键的帮助
+            Action::Help => return RunState::ShowHelp,
+
+            // 观察模式，光标从玩家的位置开始
+            Action::Look => {
+                return RunState::LookMode {
+                    cursor: *gs.ecs.fetch::<Point>(),
+                }
+            }
+
+            // 自动走到下楼的楼梯
+            Action::TravelToStairs => return travel_to_stairs(&mut gs.ecs),
+
+            // 自动探索
+            Action::AutoExplore => return auto_explore(&mut gs.ecs),
 
             // Level changes
-            VirtualKeyCode::Period => {
+            Action::Descend => {
                 if try_next_level(&mut gs.ecs) {
                     // 游戏的状态
                     return RunState::NextLevel;
@@ -222,8 +281,7 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             }
 
             // Skip Turn
-            VirtualKeyCode::Numpad5 => return skip_turn(&mut gs.ecs),
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+            Action::Wait => return skip_turn(&mut gs.ecs),
             _ => {
                 return RunState::AwaitingInput;
             }
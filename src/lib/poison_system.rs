@@ -0,0 +1,62 @@
+use specs::prelude::*;
+
+use crate::{EffectQueue, EffectType, GameLog, Poisoned, RunState, Targets};
+
+// 中毒系统
+// 中毒的实体 每回合受到伤害，持续的回合数用完后 毒性消退
+pub struct PoisonSystem {}
+
+impl<'a> System<'a> for PoisonSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Poisoned>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, GameLog>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut poisoned, player_entity, runstate, mut effects, mut log) = data;
+
+        let mut cured: Vec<Entity> = Vec::new();
+        for (entity, poison) in (&entities, &mut poisoned).join() {
+            // 和饥饿时钟一样，玩家在玩家回合结算，怪物在怪物回合结算
+            let proceed = match *runstate {
+                RunState::PlayerTurn => entity == *player_entity,
+                RunState::MonsterTurn => entity != *player_entity,
+                _ => false,
+            };
+            if !proceed {
+                continue;
+            }
+
+            effects.add_effect(
+                None,
+                EffectType::Damage {
+                    amount: poison.damage,
+                },
+                Targets::Single { target: entity },
+            );
+            if entity == *player_entity {
+                log.push(format!(
+                    "The poison burns in your veins, for {} hp.",
+                    poison.damage
+                ));
+            }
+
+            poison.turns -= 1;
+            if poison.turns < 1 {
+                cured.push(entity);
+                if entity == *player_entity {
+                    log.push("The poison wears off.");
+                }
+            }
+        }
+
+        for entity in cured.iter() {
+            poisoned.remove(*entity);
+        }
+    }
+}
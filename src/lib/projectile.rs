@@ -0,0 +1,50 @@
+use rltk::{LineAlg, Point, RGB};
+use specs::prelude::*;
+
+use crate::{tile_blocks, BlocksTile, Map, ParticleBuilder};
+
+// 投射物的飞行路线
+// 法术、远程武器 和 投掷的物品 都沿着 Bresenham 直线飞行，
+// 碰到墙就停下，碰到第一个挡路的生物 就命中它
+
+// 投射物每走一格 需要的时间
+const MS_PER_STEP: f32 = 40.0;
+
+// 从 start 飞向 target 经过的 tile，不包括 start，最后一个 tile 就是落点
+pub fn projectile_path(ecs: &World, start: Point, target: Point) -> Vec<Point> {
+    let map = ecs.fetch::<Map>();
+    let blockers = ecs.read_storage::<BlocksTile>();
+
+    let mut path = Vec::new();
+    for pt in rltk::line2d(LineAlg::Bresenham, start, target)
+        .into_iter()
+        .filter(|pt| *pt != start)
+    {
+        if pt.x < 0 || pt.x >= map.width || pt.y < 0 || pt.y >= map.height {
+            break;
+        }
+        let idx = map.xy_idx(pt.x, pt.y);
+        // 撞到墙上，停在墙前面
+        if tile_blocks(map.tiles[idx]) {
+            break;
+        }
+        path.push(pt);
+        // 命中路上第一个挡路的生物
+        if map.tile_content[idx]
+            .iter()
+            .any(|e| blockers.get(*e).is_some())
+        {
+            break;
+        }
+        if pt == target {
+            break;
+        }
+    }
+    path
+}
+
+// 沿着路线 依次显示投射物的粒子
+pub fn animate_projectile(ecs: &World, path: &[Point], glyph: rltk::FontCharType, fg: RGB) {
+    let mut particle_builder = ecs.fetch_mut::<ParticleBuilder>();
+    particle_builder.request_along_path(path, fg, RGB::named(rltk::BLACK), glyph, MS_PER_STEP);
+}
@@ -0,0 +1,240 @@
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use crate::{
+    attack_bonus, defense_bonus, Ammunition, Burden, CombatStats, ConfuseOnHit, DefenseBonus,
+    EffectQueue, EffectType, ElementalDamage, EquipmentSlot, Equipped, GameLog, HitResolver,
+    HungerClock, InBackpack, LifeSteal, Map, MeleePowerBonus, Monster, Name, Position, Quantity,
+    RangedWeapon, Renderable, RunState, Targets, Viewshed, WantsToShoot,
+};
+
+// 远程战斗系统
+// 用装备的弓弩 射出背包中对应的弹药，射中时弹药损坏，没有射中生物的弹药 落在地上 可以捡回来
+// 射中之后 和近战一样 计算伤害 和 装备词缀的效果
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Burden>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Ammunition>,
+        ReadStorage<'a, InBackpack>,
+        WriteStorage<'a, Quantity>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Renderable>,
+        // 装备词缀的命中效果
+        ReadStorage<'a, ElementalDamage>,
+        ReadStorage<'a, LifeSteal>,
+        ReadStorage<'a, ConfuseOnHit>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut log,
+            map,
+            mut wants_shoot,
+            names,
+            combat_stats,
+            melee_power_bonuses,
+            defense_bonuses,
+            equipped,
+            hunger_clock,
+            burdens,
+            ranged_weapons,
+            ammunition,
+            backpack,
+            mut quantities,
+            mut effects,
+            mut rng,
+            renderables,
+            elemental_damages,
+            life_steals,
+            confuse_on_hits,
+        ) = data;
+
+        let hits = HitResolver {
+            ranged: true,
+            names: &names,
+            renderables: &renderables,
+            equipped: &equipped,
+            elemental_damages: &elemental_damages,
+            life_steals: &life_steals,
+            confuse_on_hits: &confuse_on_hits,
+        };
+        for (entity, wants_shoot, _name, stats) in
+            (&entities, &wants_shoot, &names, &combat_stats).join()
+        {
+            if stats.hp <= 0 {
+                continue;
+            }
+            let weapon = match equipped_ranged_weapon(entity, &ranged_weapons, &equipped) {
+                Some(weapon) => weapon,
+                None => continue,
+            };
+            let ammo = match find_ammo(entity, weapon, &entities, &ammunition, &backpack) {
+                Some(ammo) => ammo,
+                None => continue,
+            };
+
+            // 落点上 还活着的生物
+            let target = wants_shoot.target;
+            let idx = map.xy_idx(target.x, target.y);
+            let victim = map.tile_content[idx]
+                .iter()
+                .copied()
+                .find(|e| *e != entity && combat_stats.get(*e).is_some_and(|s| s.hp > 0));
+
+            if let Some(victim) = victim {
+                let target_stats = combat_stats.get(victim).unwrap();
+                let offensive_bonus = attack_bonus(
+                    entity,
+                    true,
+                    &melee_power_bonuses,
+                    &equipped,
+                    &hunger_clock,
+                    &burdens,
+                );
+                let defensive_bonus = defense_bonus(victim, &defense_bonuses, &equipped, &burdens);
+                let damage = i32::max(
+                    0,
+                    (stats.power + weapon.power + offensive_bonus)
+                        - (target_stats.defense + defensive_bonus),
+                );
+                hits.hit(entity, victim, damage, &mut log, &mut effects, &mut rng);
+            }
+
+            if victim.is_some() {
+                // 射中的弹药 损坏了
+                let remaining = quantities.get(ammo).map_or(0, |q| q.amount - 1);
+                if remaining > 0 {
+                    quantities.get_mut(ammo).unwrap().amount = remaining;
+                } else {
+                    entities.delete(ammo).expect("Delete failed");
+                }
+            } else {
+                // 没有射中生物的弹药 落在落点上
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::LandItem {
+                        x: target.x,
+                        y: target.y,
+                    },
+                    Targets::Single { target: ammo },
+                );
+            }
+        }
+
+        wants_shoot.clear();
+    }
+}
+
+// 投掷的物品 是否命中，防御越高 越难命中
+pub fn ranged_hit(rng: &mut RandomNumberGenerator, defensive_bonus: i32) -> bool {
+    rng.roll_dice(1, 20) > 4 + defensive_bonus
+}
+
+// 实体装备在 Ranged 槽中的远程武器
+fn equipped_ranged_weapon<'a>(
+    owner: Entity,
+    ranged_weapons: &'a ReadStorage<RangedWeapon>,
+    equipped: &ReadStorage<Equipped>,
+) -> Option<&'a RangedWeapon> {
+    (ranged_weapons, equipped)
+        .join()
+        .find(|(_, e)| e.owner == owner && e.slot == EquipmentSlot::Ranged)
+        .map(|(weapon, _)| weapon)
+}
+
+// 背包中 和远程武器匹配的弹药
+fn find_ammo(
+    owner: Entity,
+    weapon: &RangedWeapon,
+    entities: &Entities,
+    ammunition: &ReadStorage<Ammunition>,
+    backpack: &ReadStorage<InBackpack>,
+) -> Option<Entity> {
+    (entities, ammunition, backpack)
+        .join()
+        .find(|(_, ammo, pack)| pack.owner == owner && ammo.ammo == weapon.ammo)
+        .map(|(entity, _, _)| entity)
+}
+
+// 视野中 射程内的怪物，按距离由近到远排列
+pub fn ranged_targets(ecs: &World) -> Vec<Entity> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let range = match equipped_ranged_weapon(player_entity, &ranged_weapons, &equipped) {
+        Some(weapon) => weapon.range,
+        None => return Vec::new(),
+    };
+
+    let entities = ecs.entities();
+    let monsters = ecs.read_storage::<Monster>();
+    let positions = ecs.read_storage::<Position>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let viewshed = match viewsheds.get(player_entity) {
+        Some(viewshed) => viewshed,
+        None => return Vec::new(),
+    };
+
+    let mut targets: Vec<(f32, Entity)> = Vec::new();
+    for (entity, _monster, pos) in (&entities, &monsters, &positions).join() {
+        let point = Point::new(pos.x, pos.y);
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(player_pos, point);
+        if distance <= range as f32 && viewshed.visible_tiles.contains(&point) {
+            targets.push((distance, entity));
+        }
+    }
+    targets.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    targets.into_iter().map(|(_, entity)| entity).collect()
+}
+
+// 开始瞄准，选中最近的目标
+pub fn start_firing(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let message = {
+        let entities = ecs.entities();
+        let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+        let equipped = ecs.read_storage::<Equipped>();
+        let ammunition = ecs.read_storage::<Ammunition>();
+        let backpack = ecs.read_storage::<InBackpack>();
+        match equipped_ranged_weapon(player_entity, &ranged_weapons, &equipped) {
+            None => Some("You don't have a ranged weapon equipped."),
+            Some(weapon) => {
+                if find_ammo(player_entity, weapon, &entities, &ammunition, &backpack).is_none() {
+                    Some("You are out of ammunition.")
+                } else {
+                    None
+                }
+            }
+        }
+    };
+    if let Some(message) = message {
+        ecs.fetch_mut::<GameLog>().push(message);
+        return RunState::AwaitingInput;
+    }
+
+    match ranged_targets(ecs).first() {
+        Some(target) => RunState::ShowFiring { target: *target },
+        None => {
+            ecs.fetch_mut::<GameLog>()
+                .push("There is nothing in range to shoot at.");
+            RunState::AwaitingInput
+        }
+    }
+}
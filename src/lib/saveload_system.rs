@@ -50,9 +50,18 @@ pub fn save_game(ecs: &mut World) {
     // Create helper
     // 复制一份地图
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let registrycopy = ecs
+        .get_mut::<super::identification::ItemRegistry>()
+        .unwrap()
+        .clone();
+    let logcopy = ecs.get_mut::<super::gamelog::GameLog>().unwrap().clone();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            item_registry: registrycopy,
+            game_log: logcopy,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -78,7 +87,6 @@ pub fn save_game(ecs: &mut World) {
             Name,
             BlocksTile,
             CombatStats,
-            SufferDamage,
             WantsToMelee,
             Item,
             Consumable,
@@ -104,7 +112,48 @@ pub fn save_game(ecs: &mut World) {
             Hidden,
             EntryTrigger,
             EntityMoved,
-            SingleActivation
+            SingleActivation,
+            ObfuscatedName,
+            ProvidesIdentification,
+            Cursed,
+            ProvidesRemoveCurse,
+            Charges,
+            ProvidesRecharge,
+            Quantity,
+            Weight,
+            Attributes,
+            Burden,
+            TwoHanded,
+            Wallet,
+            Gold,
+            ItemValue,
+            Vendor,
+            ElementalDamage,
+            AttributeBonus,
+            LifeSteal,
+            ConfuseOnHit,
+            Mana,
+            Spell,
+            KnownSpell,
+            TeachesSpell,
+            WantsToCastSpell,
+            RandomTeleport,
+            Alarm,
+            InflictsPoison,
+            Poisoned,
+            TrapDoor,
+            SummonsMonsters,
+            Asleep,
+            Mechanism,
+            Lever,
+            RangedWeapon,
+            Ammunition,
+            WantsToShoot,
+            Potion,
+            WantsToThrow,
+            Description,
+            KillCount,
+            Affixed
         );
     }
     // Clean up
@@ -164,7 +213,6 @@ pub fn load_game(ecs: &mut World) {
                 Name,
                 BlocksTile,
                 CombatStats,
-                SufferDamage,
                 WantsToMelee,
                 Item,
                 Consumable,
@@ -190,7 +238,48 @@ pub fn load_game(ecs: &mut World) {
                 Hidden,
                 EntryTrigger,
                 EntityMoved,
-                SingleActivation
+                SingleActivation,
+                ObfuscatedName,
+                ProvidesIdentification,
+                Cursed,
+                ProvidesRemoveCurse,
+                Charges,
+                ProvidesRecharge,
+                Quantity,
+                Weight,
+                Attributes,
+                Burden,
+                TwoHanded,
+                Wallet,
+                Gold,
+                ItemValue,
+                Vendor,
+                ElementalDamage,
+                AttributeBonus,
+                LifeSteal,
+                ConfuseOnHit,
+                Mana,
+                Spell,
+                KnownSpell,
+                TeachesSpell,
+                WantsToCastSpell,
+                RandomTeleport,
+                Alarm,
+                InflictsPoison,
+                Poisoned,
+                TrapDoor,
+                SummonsMonsters,
+                Asleep,
+                Mechanism,
+                Lever,
+                RangedWeapon,
+                Ammunition,
+                WantsToShoot,
+                Potion,
+                WantsToThrow,
+                Description,
+                KillCount,
+                Affixed
             );
         }
 
@@ -211,6 +300,14 @@ pub fn load_game(ecs: &mut World) {
                 // since we are not serializing tile_content, replace it with an empty set of vectors
                 worldmap.tile_content = vec![Vec::new(); super::map::MAPCOUNT];
 
+                // 恢复已知物品的登记表
+                let mut registry = ecs.write_resource::<super::identification::ItemRegistry>();
+                *registry = h.item_registry.clone();
+
+                // 恢复游戏日志
+                let mut game_log = ecs.write_resource::<super::gamelog::GameLog>();
+                *game_log = h.game_log.clone();
+
                 deleteme = Some(e);
             }
 
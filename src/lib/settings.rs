@@ -0,0 +1,96 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use serde::{Deserialize, Serialize};
+
+// 游戏的设置
+// 在主菜单的选项界面中修改，保存在设置文件中，启动游戏时读取
+
+pub const SETTINGS_FILE: &str = "./settings.json";
+
+// 字体
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Font {
+    Terminal8x8,
+    Vga8x16,
+}
+
+// 日志的详细程度，简略模式下 不显示没有命中之类的次要日志
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum Verbosity {
+    All,
+    Brief,
+}
+
+// 设置文件中没有的项 使用默认值
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    // 是否显示可视化地图加载
+    pub show_mapgen_visualizer: bool,
+    // 复古的扫描线效果
+    pub scanlines: bool,
+    pub font: Font,
+    // 窗口放大的倍数
+    pub scale: i32,
+    pub verbosity: Verbosity,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            show_mapgen_visualizer: true,
+            scanlines: true,
+            font: Font::Terminal8x8,
+            scale: 1,
+            verbosity: Verbosity::All,
+        }
+    }
+}
+
+// 最大的放大倍数
+pub const MAX_SCALE: i32 = 2;
+
+impl Font {
+    pub fn name(self) -> &'static str {
+        match self {
+            Font::Terminal8x8 => "Terminal 8x8",
+            Font::Vga8x16 => "VGA 8x16",
+        }
+    }
+
+    // 字符的宽和高
+    pub fn tile_size(self) -> (i32, i32) {
+        match self {
+            Font::Terminal8x8 => (8, 8),
+            Font::Vga8x16 => (8, 16),
+        }
+    }
+}
+
+impl Settings {
+    pub fn brief(&self) -> bool {
+        self.verbosity == Verbosity::Brief
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Unable to save {}: {}", SETTINGS_FILE, e))?;
+        fs::write(SETTINGS_FILE, json)
+            .map_err(|e| format!("Unable to save {}: {}", SETTINGS_FILE, e))
+    }
+}
+
+// 读取设置文件，没有设置文件时 使用默认的设置
+pub fn load_settings() -> Result<Settings, String> {
+    match fs::read_to_string(SETTINGS_FILE) {
+        Ok(json) => serde_json::from_str(&json)
+            .map(|settings: Settings| Settings {
+                scale: settings.scale.clamp(1, MAX_SCALE),
+                ..settings
+            })
+            .map_err(|e| format!("Invalid settings in {}: {}", SETTINGS_FILE, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(format!("Unable to read {}: {}", SETTINGS_FILE, e)),
+    }
+}
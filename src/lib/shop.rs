@@ -0,0 +1,79 @@
+use specs::prelude::*;
+
+use crate::*;
+
+// 商店的买卖，商人的货物放在商人的背包中
+
+// 卖给商人的价格 是基础价格的一半
+pub fn sell_price(value: i32) -> i32 {
+    i32::max(1, value / 2)
+}
+
+pub fn item_value(ecs: &World, item: Entity) -> i32 {
+    ecs.read_storage::<ItemValue>()
+        .get(item)
+        .map_or(0, |v| v.value)
+}
+
+// 一个物品的重量，成堆的物品 每次只买卖其中的一个
+fn item_weight(ecs: &World, item: Entity) -> f32 {
+    ecs.read_storage::<Weight>()
+        .get(item)
+        .map_or(0.0, |w| w.weight)
+}
+
+// 从商人那里买一个物品
+pub fn buy_item(ecs: &mut World, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let price = item_value(ecs, item);
+    let name = get_item_display_name(ecs, item);
+    let gold = ecs
+        .read_storage::<Wallet>()
+        .get(player_entity)
+        .map_or(0, |w| w.gold);
+
+    if gold < price {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("You cannot afford {}.", name));
+        return;
+    }
+
+    // 和捡起物品一样，超过负重上限太多 就拿不动了
+    let weight = item_weight(ecs, item);
+    if let Some(burden) = ecs.write_storage::<Burden>().get_mut(player_entity) {
+        if burden.carried + weight > burden.capacity * MAX_CARRY_MULTIPLIER {
+            ecs.fetch_mut::<GameLog>()
+                .push(format!("You cannot carry {}, it is too heavy.", name));
+            return;
+        }
+        burden.carried += weight;
+    }
+
+    if let Some(wallet) = ecs.write_storage::<Wallet>().get_mut(player_entity) {
+        wallet.gold -= price;
+    }
+    let single = split_stack(ecs, item);
+    add_to_backpack(ecs, single, player_entity);
+    ecs.fetch_mut::<GameLog>()
+        .push(format!("You buy {} for {} gold.", name, price));
+}
+
+// 把背包中的一个物品 卖给商人
+pub fn sell_item(ecs: &mut World, vendor: Entity, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let price = sell_price(item_value(ecs, item));
+    let name = get_item_display_name(ecs, item);
+
+    let weight = item_weight(ecs, item);
+    if let Some(burden) = ecs.write_storage::<Burden>().get_mut(player_entity) {
+        burden.carried = f32::max(0.0, burden.carried - weight);
+    }
+
+    let single = split_stack(ecs, item);
+    add_to_backpack(ecs, single, vendor);
+    if let Some(wallet) = ecs.write_storage::<Wallet>().get_mut(player_entity) {
+        wallet.gold += price;
+    }
+    ecs.fetch_mut::<GameLog>()
+        .push(format!("You sell {} for {} gold.", name, price));
+}
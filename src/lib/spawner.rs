@@ -25,9 +25,64 @@ fn room_table(map_depth: i32) -> RandomTable {
         .add("Shield", 3)
         .add("Longsword", map_depth - 1)
         .add("Tower Shield", map_depth - 1)
+        .add("Cursed Longsword", map_depth - 1)
+        .add("Cursed Tower Shield", map_depth - 1)
+        .add("Greatsword", map_depth - 1)
+        .add("Leather Armor", 2)
+        .add("Leather Cap", 2)
+        .add("Leather Leggings", 2)
+        .add("Leather Boots", 2)
+        .add("Leather Gloves", 2)
+        .add("Ring of Power", map_depth - 1)
+        .add("Amulet of Protection", map_depth - 1)
         .add("Rations", 10)
+        .add("Wand of Magic Missile", map_depth)
+        .add("Wand of Fireball", map_depth - 1)
+        .add("Recharge Scroll", map_depth - 1)
         .add("Magic Mapping Scroll", 2)
+        .add("Identify Scroll", 3)
+        .add("Remove Curse Scroll", map_depth - 1)
         .add("Bear Trap", 2)
+        .add("Teleport Trap", map_depth - 1)
+        .add("Alarm Trap", map_depth)
+        .add("Poison Dart Trap", map_depth)
+        .add("Pit Trap", map_depth - 2)
+        .add("Summoning Trap", map_depth - 2)
+        .add("Gold", 4)
+        .add("Whetstone", 2)
+        .add("Shortbow", 2)
+        .add("Crossbow", map_depth - 1)
+        .add("Arrows", 3)
+        .add("Bolts", map_depth - 1)
+        .add("Spellbook of Magic Missile", 2)
+        .add("Spellbook of Confusion", map_depth - 1)
+        .add("Spellbook of Healing", map_depth - 1)
+        .add("Spellbook of Fireball", map_depth - 2)
+}
+
+// 商人出售的货物，同样随着地图深度变化
+fn vendor_table(map_depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Health Potion", 7)
+        .add("Rations", 10)
+        .add("Magic Missile Scroll", 4)
+        .add("Fireball Scroll", 2 + map_depth)
+        .add("Confusion Scroll", 2 + map_depth)
+        .add("Identify Scroll", 3)
+        .add("Remove Curse Scroll", map_depth - 1)
+        .add("Recharge Scroll", map_depth - 1)
+        .add("Dagger", 3)
+        .add("Shield", 3)
+        .add("Shortbow", 2)
+        .add("Arrows", 4)
+        .add("Bolts", map_depth - 1)
+        .add("Leather Armor", 2)
+        .add("Leather Cap", 2)
+        .add("Leather Boots", 2)
+        .add("Longsword", map_depth - 1)
+        .add("Tower Shield", map_depth - 1)
+        .add("Wand of Magic Missile", map_depth)
+        .add("Whetstone", 2)
 }
 
 #[allow(clippy::map_entry)]
@@ -52,21 +107,130 @@ pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
 fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     let x = (*spawn.0 % MAPWIDTH) as i32;
     let y = (*spawn.0 / MAPWIDTH) as i32;
+    spawn_named(ecs, x, y, spawn.1);
+}
+
+// 根据名字生成一个物品，直接放进 owner 的背包
+pub fn spawn_in_backpack(ecs: &mut World, name: &str, owner: Entity) {
+    let (x, y) = ecs
+        .read_storage::<Position>()
+        .get(owner)
+        .map_or((0, 0), |pos| (pos.x, pos.y));
 
-    match spawn.1.as_ref() {
+    // 物品先生成在 owner 的位置上，再放进背包
+    let existing: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<Item>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect();
+    spawn_named(ecs, x, y, name);
+    let spawned: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<Item>())
+        .join()
+        .map(|(entity, _)| entity)
+        .filter(|entity| !existing.contains(entity))
+        .collect();
+    for item in spawned {
+        add_to_backpack(ecs, item, owner);
+    }
+}
+
+// 根据名字在指定的位置 生成实体
+pub fn spawn_named(ecs: &mut World, x: i32, y: i32, name: &str) {
+    // 武器和护甲 可能带有随机的词缀
+    let equipment = match name {
+        "Dagger" => Some(dagger(ecs, x, y)),
+        "Shield" => Some(shield(ecs, x, y)),
+        "Longsword" => Some(longsword(ecs, x, y)),
+        "Tower Shield" => Some(tower_shield(ecs, x, y)),
+        "Greatsword" => Some(greatsword(ecs, x, y)),
+        "Leather Armor" => Some(armor(
+            ecs,
+            x,
+            y,
+            "Leather Armor",
+            EquipmentSlot::Torso,
+            1,
+            10.0,
+        )),
+        "Leather Cap" => Some(armor(ecs, x, y, "Leather Cap", EquipmentSlot::Head, 1, 1.0)),
+        "Leather Leggings" => Some(armor(
+            ecs,
+            x,
+            y,
+            "Leather Leggings",
+            EquipmentSlot::Legs,
+            1,
+            5.0,
+        )),
+        "Leather Boots" => Some(armor(
+            ecs,
+            x,
+            y,
+            "Leather Boots",
+            EquipmentSlot::Feet,
+            1,
+            2.0,
+        )),
+        "Leather Gloves" => Some(armor(
+            ecs,
+            x,
+            y,
+            "Leather Gloves",
+            EquipmentSlot::Hands,
+            1,
+            1.0,
+        )),
+        _ => None,
+    };
+    if let Some(item) = equipment {
+        let depth = ecs.fetch::<Map>().depth;
+        roll_affixes(ecs, item, depth);
+        return;
+    }
+
+    match name {
         "Goblin" => goblin(ecs, x, y),
         "Orc" => orc(ecs, x, y),
         "Health Potion" => health_potion(ecs, x, y),
         "Fireball Scroll" => fireball_scroll(ecs, x, y),
         "Confusion Scroll" => confusion_scroll(ecs, x, y),
         "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-        "Dagger" => dagger(ecs, x, y),
-        "Shield" => shield(ecs, x, y),
-        "Longsword" => longsword(ecs, x, y),
-        "Tower Shield" => tower_shield(ecs, x, y),
+        "Cursed Longsword" => cursed_longsword(ecs, x, y),
+        "Cursed Tower Shield" => cursed_tower_shield(ecs, x, y),
+        "Ring of Power" => ring_of_power(ecs, x, y),
+        "Amulet of Protection" => amulet_of_protection(ecs, x, y),
         "Rations" => rations(ecs, x, y),
         "Magic Mapping Scroll" => magic_mapping_scroll(ecs, x, y),
+        "Identify Scroll" => identify_scroll(ecs, x, y),
+        "Remove Curse Scroll" => remove_curse_scroll(ecs, x, y),
+        "Wand of Magic Missile" => magic_missile_wand(ecs, x, y),
+        "Wand of Fireball" => fireball_wand(ecs, x, y),
+        "Recharge Scroll" => recharge_scroll(ecs, x, y),
         "Bear Trap" => bear_trap(ecs, x, y),
+        "Teleport Trap" => teleport_trap(ecs, x, y),
+        "Alarm Trap" => alarm_trap(ecs, x, y),
+        "Poison Dart Trap" => poison_dart_trap(ecs, x, y),
+        "Pit Trap" => pit_trap(ecs, x, y),
+        "Summoning Trap" => summoning_trap(ecs, x, y),
+        // 合成出来的装备 已经加强过了，不再随机加词缀
+        "Sharp Dagger" => sharp_dagger(ecs, x, y),
+        "Gold" => {
+            let depth = ecs.fetch::<Map>().depth;
+            let amount = ecs
+                .write_resource::<RandomNumberGenerator>()
+                .roll_dice(2, 6 + depth * 2);
+            gold(ecs, x, y, amount)
+        }
+        "Shopkeeper" => shopkeeper(ecs, x, y),
+        "Whetstone" => whetstone(ecs, x, y),
+        "Travel Meal" => travel_meal(ecs, x, y),
+        "Shortbow" => ranged_weapon(ecs, x, y, "Shortbow", 6, 3, AmmoType::Arrow),
+        "Crossbow" => ranged_weapon(ecs, x, y, "Crossbow", 8, 5, AmmoType::Bolt),
+        "Arrows" => ammunition(ecs, x, y, "Arrows", AmmoType::Arrow),
+        "Bolts" => ammunition(ecs, x, y, "Bolts", AmmoType::Bolt),
+        "Spellbook of Magic Missile" => spellbook(ecs, x, y, "Magic Missile"),
+        "Spellbook of Confusion" => spellbook(ecs, x, y, "Confusion"),
+        "Spellbook of Healing" => spellbook(ecs, x, y, "Healing"),
+        "Spellbook of Fireball" => spellbook(ecs, x, y, "Fireball"),
         _ => {}
     }
 }
@@ -115,7 +279,8 @@ pub fn spawn_region(ecs: &mut World, area: &[usize], map_depth: i32) {
 
 /// spawn the player and return his/her entity object
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
-    ecs.create_entity()
+    let player = ecs
+        .create_entity()
         // with 各种组件
         .with(Position {
             x: player_x,
@@ -137,6 +302,11 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .with(Name {
             name: "Player".to_string(),
         })
+        .with(Description {
+            text:
+                "That's you, a lone adventurer looking for fame and fortune in the dungeon below."
+                    .to_string(),
+        })
         .with(CombatStats {
             max_hp: 30,
             hp: 30,
@@ -148,8 +318,25 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             state: HungerState::WellFed,
             duration: 20,
         })
+        .with(Attributes {
+            strength: 10,
+            perception: 10,
+        })
+        .with(Wallet { gold: 0 })
+        .with(Burden {
+            carried: 0.0,
+            capacity: carry_capacity(10),
+        })
+        .with(KillCount { kills: 0 })
+        .with(Mana {
+            current: 10,
+            max: 10,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build()
+        .build();
+    // 玩家一开始就会 魔法飞弹
+    spell(ecs, "Magic Missile", player);
+    player
 }
 
 // ---------------------------------monster-------------------------------
@@ -169,15 +356,47 @@ pub fn random_monster(ecs: &mut World, x: i32, y: i32) {
 }
 
 fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('o'),
+        "Orc",
+        "A hulking green brute, armed with crude weapons and a worse temper.",
+    );
 }
 fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('g'),
+        "Goblin",
+        "A small, wiry creature that makes up for its size with cunning and spite.",
+    );
 }
 
 // 泛型 S 实现呢ToString trait
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
-    ecs.create_entity()
+fn monster<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    name: S,
+    description: &str,
+) {
+    // 怪物身上带着一些金币，死后掉落
+    let gold = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 8)
+        - 3;
+    // 有些怪物 一开始是睡着的
+    let asleep = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 3)
+        == 1;
+    let monster = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph,
@@ -194,6 +413,9 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharTy
         .with(Name {
             name: name.to_string(),
         })
+        .with(Description {
+            text: description.to_string(),
+        })
         .with(BlocksTile {})
         .with(CombatStats {
             max_hp: 16,
@@ -201,10 +423,121 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharTy
             defense: 1,
             power: 4,
         })
+        .with(Attributes {
+            strength: 8,
+            perception: 8,
+        })
+        .with(Wallet {
+            gold: i32::max(0, gold),
+        })
+        .with(Burden {
+            carried: 0.0,
+            capacity: carry_capacity(8),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    if asleep {
+        ecs.write_storage::<Asleep>()
+            .insert(monster, Asleep {})
+            .expect("Unable to insert asleep");
+    }
+}
+// ---------------------------------vendor-------------------------------
+// 商人，不会攻击 也不能被攻击，撞上去就可以交易
+fn shopkeeper(ecs: &mut World, x: i32, y: i32) {
+    let vendor = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Name {
+            name: "Shopkeeper".to_string(),
+        })
+        .with(Description {
+            text: "A shrewd merchant who will buy and sell just about anything, at a price."
+                .to_string(),
+        })
+        .with(BlocksTile {})
+        .with(Vendor {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
+
+    // 从商人的生成表中 挑选货物
+    let depth = ecs.fetch::<Map>().depth;
+    let stock: Vec<String> = {
+        let table = vendor_table(depth);
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let count = 4 + depth;
+        (0..count).map(|_| table.roll(&mut rng)).collect()
+    };
+    for name in stock.iter() {
+        spawn_in_backpack(ecs, name, vendor);
+    }
 }
+
+// 城镇中的村民，不会攻击 也不能被攻击
+pub fn townsperson(ecs: &mut World, x: i32, y: i32, name: &str) {
+    let description = match name {
+        "Barkeep" => "The barkeep polishes a mug and keeps a watchful eye on the patrons.",
+        "Patron" => "A regular of the tavern, nursing an ale and swapping rumours.",
+        "Peasant" => "A farmer on the way to market, in no hurry at all.",
+        _ => "One of the townsfolk, going about their day.",
+    };
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::LIGHT_GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Description {
+            text: description.to_string(),
+        })
+        .with(BlocksTile {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 // ----------------------------item------------------------------------
+// 一堆金币
+pub fn gold(ecs: &mut World, x: i32, y: i32, amount: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('$'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Gold".to_string(),
+        })
+        .with(Description {
+            text: "A handful of gold coins. Shopkeepers will gladly take them.".to_string(),
+        })
+        .with(Item {})
+        .with(Gold { amount })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 卷轴和药水在鉴定之前显示的名字，由已知物品登记表分配
+fn obfuscated_name(ecs: &mut World, real_name: &str, class: MagicItemClass) -> ObfuscatedName {
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let mut registry = ecs.write_resource::<ItemRegistry>();
+    ObfuscatedName {
+        name: registry.obfuscated_name(real_name, class, &mut rng),
+    }
+}
+
 fn random_item(ecs: &mut World, x: i32, y: i32) {
     let roll: i32;
     {
@@ -223,6 +556,7 @@ fn random_item(ecs: &mut World, x: i32, y: i32) {
 // 组件表明 它是一个item consumed on use, provide 8 points of healing
 // 组件可以混合，随着组件的添加，物品系统变得更加灵活
 fn health_potion(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Health Potion", MagicItemClass::Potion);
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -234,8 +568,16 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Health Potion".to_string(),
         })
+        .with(Description {
+            text: "A small vial of red liquid. Drinking it closes wounds; thrown, it shatters and heals everyone nearby.".to_string(),
+        })
+        .with(obfuscated)
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 50 })
         .with(Consumable {})
+        .with(Potion {})
+        .with(Quantity { amount: 1 })
         .with(ProvidesHealing { heal_amount: 8 }) // 恢复生命值的数量 组件
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
@@ -247,6 +589,7 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
 // device than writing many lines of code
 // 如果的你的工作可以被AI取代，那么说明你还不够厉害 missile 导弹
 fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Magic Missile Scroll", MagicItemClass::Scroll);
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -258,8 +601,16 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Magic Missile Scroll".to_string(),
         })
+        .with(Description {
+            text: "A scroll inscribed with a spell that hurls a bolt of force at a single target."
+                .to_string(),
+        })
+        .with(obfuscated)
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 50 })
         .with(Consumable {})
+        .with(Quantity { amount: 1 })
         .with(Ranged { range: 6 })
         .with(InflictsDamage { damage: 8 })
         .marked::<SimpleMarker<SerializeMe>>()
@@ -268,6 +619,7 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
 
 // 生成 火球攻击
 fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Fireball Scroll", MagicItemClass::Scroll);
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -279,8 +631,16 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Fireball Scroll".to_string(),
         })
+        .with(Description {
+            text: "A scroll that unleashes a ball of fire, burning everything caught in the blast."
+                .to_string(),
+        })
+        .with(obfuscated)
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 100 })
         .with(Consumable {})
+        .with(Quantity { amount: 1 })
         .with(Ranged { range: 6 })
         // and they would inflict damage on a single entity. Clearly, we must fix that!
         .with(InflictsDamage { damage: 20 })
@@ -292,6 +652,7 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
 
 // create confusin scrolls
 fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Confusion Scroll", MagicItemClass::Scroll);
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -303,8 +664,16 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Confusion Scroll".to_string(),
         })
+        .with(Description {
+            text: "A scroll whose spell leaves its target dazed and stumbling for a few turns."
+                .to_string(),
+        })
+        .with(obfuscated)
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 75 })
         .with(Consumable {})
+        .with(Quantity { amount: 1 })
         .with(Ranged { range: 6 })
         // Confusion Components
         // turns 4 个回合
@@ -319,7 +688,7 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
 
 //---------------------------------------- weapon and shield ------------------------
 // 匕首
-fn dagger(ecs: &mut World, x: i32, y: i32) {
+fn dagger(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x: x, y: y })
         .with(Renderable {
@@ -331,18 +700,24 @@ fn dagger(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Dagger".to_string(),
         })
+        .with(Description {
+            text: "A short, light blade. Easy to wield, and well balanced for throwing."
+                .to_string(),
+        })
         .with(Item {})
+        .with(Weight { weight: 1.0 })
+        .with(ItemValue { value: 10 })
         .with(Equippable {
-            slot: EquipmentSlot::Shield,
+            slot: EquipmentSlot::Melee,
         })
         .with(MeleePowerBonus { power: 2 })
         // 序列化
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
 // 盾牌
-fn shield(ecs: &mut World, x: i32, y: i32) {
+fn shield(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -354,18 +729,23 @@ fn shield(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Shield".to_string(),
         })
+        .with(Description {
+            text: "A round wooden shield banded with iron.".to_string(),
+        })
         .with(Item {})
+        .with(Weight { weight: 5.0 })
+        .with(ItemValue { value: 15 })
         .with(Equippable {
             slot: EquipmentSlot::Shield,
         })
         .with(DefenseBonus { defense: 1 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
 // 更多的装备
 // 长剑
-fn longsword(ecs: &mut World, x: i32, y: i32) {
+fn longsword(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -377,17 +757,22 @@ fn longsword(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Longsword".to_string(),
         })
+        .with(Description {
+            text: "A well balanced steel sword with a long, straight blade.".to_string(),
+        })
         .with(Item {})
+        .with(Weight { weight: 3.0 })
+        .with(ItemValue { value: 50 })
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
         .with(MeleePowerBonus { power: 4 })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .build()
 }
 
 // 塔盾
-fn tower_shield(ecs: &mut World, x: i32, y: i32) {
+fn tower_shield(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -399,12 +784,290 @@ fn tower_shield(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Tower Shield".to_string(),
         })
+        .with(Description {
+            text: "A huge shield that covers its bearer from chin to shin. Heavy, but very sturdy."
+                .to_string(),
+        })
         .with(Item {})
+        .with(Weight { weight: 10.0 })
+        .with(ItemValue { value: 60 })
         .with(Equippable {
             slot: EquipmentSlot::Shield,
         })
         .with(DefenseBonus { defense: 3 })
         .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+// 被诅咒的长剑，在装备或者鉴定之前，看起来和普通的长剑一样
+fn cursed_longsword(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Cursed Longsword".to_string(),
+        })
+        .with(Description {
+            text: "A well balanced steel sword with a long, straight blade.".to_string(),
+        })
+        .with(ObfuscatedName {
+            name: "Longsword".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 3.0 })
+        .with(ItemValue { value: 50 })
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleePowerBonus { power: -2 })
+        .with(Cursed {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 被诅咒的塔盾
+fn cursed_tower_shield(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('('),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Cursed Tower Shield".to_string(),
+        })
+        .with(Description {
+            text: "A huge shield that covers its bearer from chin to shin. Heavy, but very sturdy."
+                .to_string(),
+        })
+        .with(ObfuscatedName {
+            name: "Tower Shield".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 10.0 })
+        .with(ItemValue { value: 60 })
+        .with(Equippable {
+            slot: EquipmentSlot::Shield,
+        })
+        .with(DefenseBonus { defense: -1 })
+        .with(Cursed {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 双手大剑，装备后不能再使用盾牌
+fn greatsword(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Greatsword".to_string(),
+        })
+        .with(Description {
+            text: "A massive blade that takes both hands to swing, leaving no room for a shield."
+                .to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 8.0 })
+        .with(ItemValue { value: 80 })
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(TwoHanded {})
+        .with(MeleePowerBonus { power: 6 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+// 观察模式中 护甲 的描述
+fn armor_description(name: &str) -> &'static str {
+    match name {
+        "Leather Cap" => "A snug leather cap that takes the sting out of a blow to the head.",
+        "Leather Leggings" => "Leather leggings, stiff but protective.",
+        "Leather Boots" => "Sturdy leather boots, well worn in.",
+        "Leather Gloves" => "Thick leather gloves that protect the hands.",
+        _ => "A jerkin of boiled leather. It won't stop a determined blade, but it helps.",
+    }
+}
+
+// 只提供防御的护甲，头 身体 腿 脚 手
+fn armor(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    slot: EquipmentSlot,
+    defense: i32,
+    weight: f32,
+) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('['),
+            fg: RGB::named(rltk::BROWN1),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Description {
+            text: armor_description(name).to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight })
+        .with(ItemValue {
+            value: defense * 20,
+        })
+        .with(Equippable { slot })
+        .with(DefenseBonus { defense })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+// 力量戒指
+fn ring_of_power(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('='),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Ring of Power".to_string(),
+        })
+        .with(Description {
+            text: "A gold ring that hums faintly, lending extra force to every blow.".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 0.1 })
+        .with(ItemValue { value: 100 })
+        .with(Equippable {
+            slot: EquipmentSlot::Ring,
+        })
+        .with(MeleePowerBonus { power: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 守护护符
+fn amulet_of_protection(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('"'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Amulet of Protection".to_string(),
+        })
+        .with(Description {
+            text: "A silver amulet that turns aside some of the blows aimed at its wearer."
+                .to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 0.1 })
+        .with(ItemValue { value: 100 })
+        .with(Equippable {
+            slot: EquipmentSlot::Amulet,
+        })
+        .with(DefenseBonus { defense: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// ----------------------------crafting------------------------------------
+// 磨刀石，可以和匕首一起做成锋利的匕首
+fn whetstone(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('*'),
+            fg: RGB::named(rltk::GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Whetstone".to_string(),
+        })
+        .with(Description {
+            text: "A fine-grained stone, used to hone a blade to a keener edge.".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 1.0 })
+        .with(ItemValue { value: 5 })
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 两份口粮做成的旅行餐，比两份口粮轻
+fn travel_meal(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Travel Meal".to_string(),
+        })
+        .with(Description {
+            text: "Hearty food packed for the road. It keeps you fed for a long time.".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 15 })
+        .with(ProvidesFood {})
+        .with(ProvidesHealing { heal_amount: 5 })
+        .with(Consumable {})
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 磨过的匕首
+fn sharp_dagger(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Sharp Dagger".to_string(),
+        })
+        .with(Description {
+            text: "A dagger honed to a razor edge.".to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 1.0 })
+        .with(ItemValue { value: 30 })
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleePowerBonus { power: 3 })
+        .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
@@ -421,15 +1084,22 @@ fn rations(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Rations".to_string(),
         })
+        .with(Description {
+            text: "Dried meat and hard bread. Not tasty, but filling.".to_string(),
+        })
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 5 })
         .with(ProvidesFood {})
         .with(Consumable {})
+        .with(Quantity { amount: 1 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
 // magic mapping
 fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Scroll of Magic Mapping", MagicItemClass::Scroll);
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -441,13 +1111,354 @@ fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Scroll of Magic Mapping".to_string(),
         })
+        .with(Description {
+            text: "A scroll that reveals the layout of the whole level when read.".to_string(),
+        })
+        .with(obfuscated)
         .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 75 })
         .with(MagicMapper {})
         .with(Consumable {})
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 鉴定卷轴
+fn identify_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Scroll of Identify", MagicItemClass::Scroll);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Identify".to_string(),
+        })
+        .with(Description {
+            text: "A scroll that reveals the true nature of one unknown item.".to_string(),
+        })
+        .with(obfuscated)
+        .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 40 })
+        .with(ProvidesIdentification {})
+        .with(Consumable {})
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 解除诅咒卷轴
+fn remove_curse_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Scroll of Remove Curse", MagicItemClass::Scroll);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::LIGHT_BLUE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Remove Curse".to_string(),
+        })
+        .with(Description {
+            text: "A scroll that breaks the curse on any equipment you are wearing.".to_string(),
+        })
+        .with(obfuscated)
+        .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 60 })
+        .with(ProvidesRemoveCurse {})
+        .with(Consumable {})
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 充能卷轴
+fn recharge_scroll(ecs: &mut World, x: i32, y: i32) {
+    let obfuscated = obfuscated_name(ecs, "Scroll of Recharging", MagicItemClass::Scroll);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Recharging".to_string(),
+        })
+        .with(Description {
+            text: "A scroll that restores the charges of the wands in your pack.".to_string(),
+        })
+        .with(obfuscated)
+        .with(Item {})
+        .with(Weight { weight: 0.5 })
+        .with(ItemValue { value: 80 })
+        .with(ProvidesRecharge {})
+        .with(Consumable {})
+        .with(Quantity { amount: 1 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// --------------------------------------ranged weapon------------------------------------------------
+// 观察模式中 远程武器 的描述
+fn ranged_weapon_description(name: &str) -> &'static str {
+    match name {
+        "Crossbow" => "A heavy crossbow that hits hard and far. It needs bolts to shoot.",
+        _ => "A light bow of bent yew. It needs arrows to shoot.",
+    }
+}
+
+// 观察模式中 弹药 的描述
+fn ammunition_description(name: &str) -> &'static str {
+    match name {
+        "Bolts" => "A bundle of short, stubby bolts for a crossbow.",
+        _ => "A bundle of fletched arrows for a bow.",
+    }
+}
+
+// 弓 和 弩
+fn ranged_weapon(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    range: i32,
+    power: i32,
+    ammo: AmmoType,
+) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Description {
+            text: ranged_weapon_description(name).to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 3.0 })
+        .with(ItemValue { value: power * 15 })
+        .with(Equippable {
+            slot: EquipmentSlot::Ranged,
+        })
+        .with(RangedWeapon { range, power, ammo })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 一捆箭 或者 弩箭
+fn ammunition(ecs: &mut World, x: i32, y: i32, name: &str, ammo: AmmoType) {
+    let amount = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(2, 6);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('↑'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Description {
+            text: ammunition_description(name).to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 0.1 })
+        .with(ItemValue { value: 1 })
+        .with(Quantity { amount })
+        .with(Ammunition { ammo })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// --------------------------------------spell------------------------------------------------
+// 法术书，阅读后学会书中的法术
+fn spellbook(ecs: &mut World, x: i32, y: i32, spell: &str) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¶'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: format!("Spellbook of {}", spell),
+        })
+        .with(Description {
+            text: format!(
+                "A worn book of magic. Studying it teaches the spell {}.",
+                spell
+            ),
+        })
+        .with(Item {})
+        .with(Weight { weight: 2.0 })
+        .with(ItemValue { value: 150 })
+        .with(Consumable {})
+        .with(TeachesSpell {
+            spell: spell.to_string(),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 为 owner 创建一个学会的法术，法术没有位置，和背包中的物品一样
+pub fn spell(ecs: &mut World, name: &str, owner: Entity) {
+    let builder = ecs
+        .create_entity()
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(KnownSpell { owner });
+    let builder = match name {
+        "Magic Missile" => builder
+            .with(Spell { mana_cost: 2 })
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage { damage: 6 }),
+        "Confusion" => builder
+            .with(Spell { mana_cost: 3 })
+            .with(Ranged { range: 6 })
+            .with(Confusion { turns: 4 }),
+        "Healing" => builder
+            .with(Spell { mana_cost: 4 })
+            .with(ProvidesHealing { heal_amount: 8 }),
+        "Fireball" => builder
+            .with(Spell { mana_cost: 6 })
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage { damage: 15 })
+            .with(AreaOfEffect { radius: 3 }),
+        _ => builder,
+    };
+    builder.marked::<SimpleMarker<SerializeMe>>().build();
+}
+
+// --------------------------------------wand------------------------------------------------
+// 魔法导弹魔杖，充能用完后保留，可以用充能卷轴恢复
+fn magic_missile_wand(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Wand of Magic Missile".to_string(),
+        })
+        .with(Description {
+            text: "A slender wand that fires bolts of force for as long as its charges last."
+                .to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 1.0 })
+        .with(ItemValue { value: 150 })
+        .with(Charges { current: 3, max: 3 })
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 8 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 火球魔杖，充能用完后碎掉
+fn fireball_wand(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Wand of Fireball".to_string(),
+        })
+        .with(Description {
+            text: "A scorched wand that hurls fireballs for as long as its charges last."
+                .to_string(),
+        })
+        .with(Item {})
+        .with(Weight { weight: 1.0 })
+        .with(ItemValue { value: 200 })
+        .with(Consumable {})
+        .with(Charges { current: 2, max: 2 })
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 20 })
+        .with(AreaOfEffect { radius: 3 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// --------------------------------------mechanism------------------------------------------------
+// 拉杆，撞上去就会拉动，触发远处的机关
+pub fn lever(ecs: &mut World, x: i32, y: i32, mechanism: Mechanism) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('/'),
+            fg: RGB::named(rltk::LIGHT_GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Lever".to_string(),
+        })
+        .with(Description {
+            text: "A rusty lever set into the wall. Something, somewhere, is connected to it."
+                .to_string(),
+        })
+        .with(BlocksTile {})
+        .with(Lever { pulled: false })
+        .with(mechanism)
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
+// 压力板，踩上去就会触发远处的机关
+pub fn pressure_plate(ecs: &mut World, x: i32, y: i32, mechanism: Mechanism, once: bool) {
+    let builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('_'),
+            fg: RGB::named(rltk::LIGHT_GRAY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Pressure Plate".to_string(),
+        })
+        .with(Description {
+            text: "A stone slab that sinks slightly underfoot. Something, somewhere, is connected to it.".to_string(),
+        })
+        .with(EntryTrigger {})
+        .with(mechanism);
+    let builder = if once {
+        builder.with(SingleActivation {})
+    } else {
+        builder
+    };
+    builder.marked::<SimpleMarker<SerializeMe>>().build();
+}
+
 // --------------------------------------trap------------------------------------------------
 // bear_trap
 fn bear_trap(ecs: &mut World, x: i32, y: i32) {
@@ -462,6 +1473,9 @@ fn bear_trap(ecs: &mut World, x: i32, y: i32) {
         .with(Name {
             name: "Bear Trap".to_string(),
         })
+        .with(Description {
+            text: "A pair of spring-loaded iron jaws, waiting for a careless foot.".to_string(),
+        })
         .with(Hidden {})
         .with(EntryTrigger {})
         .with(InflictsDamage { damage: 6 })
@@ -470,4 +1484,82 @@ fn bear_trap(ecs: &mut World, x: i32, y: i32) {
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
+
+// 传送陷阱，可以反复触发
+fn teleport_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Teleport Trap", RGB::named(rltk::MAGENTA))
+        .with(Description {
+            text: "A circle of glowing runes that flings whoever steps on it somewhere else on the level.".to_string(),
+        })
+        .with(RandomTeleport {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 警报陷阱，叫醒附近所有的怪物
+fn alarm_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Alarm Trap", RGB::named(rltk::YELLOW))
+        .with(Description {
+            text: "A tripwire strung to a bell loud enough to wake the whole level.".to_string(),
+        })
+        .with(Alarm { radius: 15 })
+        .with(SingleActivation {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 毒镖陷阱
+fn poison_dart_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Poison Dart Trap", RGB::named(rltk::GREEN))
+        .with(Description {
+            text: "A hidden pressure plate that fires a poisoned dart from the wall.".to_string(),
+        })
+        .with(InflictsDamage { damage: 2 })
+        .with(InflictsPoison {
+            turns: 5,
+            damage: 1,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 陷坑，掉进去就到了下一层
+fn pit_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Pit Trap", RGB::named(rltk::GRAY))
+        .with(Description {
+            text: "A thin cover hiding a deep shaft down to the next level.".to_string(),
+        })
+        .with(TrapDoor {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 召唤陷阱，在周围召唤出怪物
+fn summoning_trap(ecs: &mut World, x: i32, y: i32) {
+    trap(ecs, x, y, "Summoning Trap", RGB::named(rltk::PURPLE))
+        .with(Description {
+            text: "A ring of dark sigils that calls monsters out of thin air.".to_string(),
+        })
+        .with(SummonsMonsters { count: 3 })
+        .with(SingleActivation {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+// 陷阱共有的组件
+fn trap<'a>(ecs: &'a mut World, x: i32, y: i32, name: &str, fg: RGB) -> EntityBuilder<'a> {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {})
+}
 // 然后在main.rs 中使用该模块的生成函数，创建 玩家 和 怪物 物品
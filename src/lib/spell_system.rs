@@ -0,0 +1,90 @@
+use specs::prelude::*;
+
+use crate::{
+    AreaOfEffect, EffectQueue, EffectType, GameLog, Mana, Map, Name, Spell, Targets,
+    WantsToCastSpell,
+};
+
+// 施法系统
+// 检查并扣除法力，法术的效果 和 物品一样交给效果系统处理
+pub struct SpellCastSystem {}
+
+impl<'a> System<'a> for SpellCastSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToCastSpell>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Spell>,
+        ReadStorage<'a, AreaOfEffect>,
+        WriteStorage<'a, Mana>,
+        WriteExpect<'a, EffectQueue>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            map,
+            entities,
+            mut wants_cast,
+            names,
+            spells,
+            aoe,
+            mut mana,
+            mut effects,
+        ) = data;
+
+        for (entity, cast) in (&entities, &wants_cast).join() {
+            let spell_name = &names.get(cast.spell).unwrap().name;
+            // 没有法力的实体 无法施法
+            let mana = match mana.get_mut(entity) {
+                Some(mana) => mana,
+                None => {
+                    if entity == *player_entity {
+                        gamelog.push(format!("You cannot cast {}.", spell_name));
+                    }
+                    continue;
+                }
+            };
+            let cost = spells.get(cast.spell).map_or(0, |s| s.mana_cost);
+            // 法力不够，施法失败，但仍然消耗这一回合
+            if mana.current < cost {
+                if entity == *player_entity {
+                    gamelog.push(format!(
+                        "You don't have enough mana to cast {}.",
+                        spell_name
+                    ));
+                }
+                continue;
+            }
+            mana.current -= cost;
+            if entity == *player_entity {
+                gamelog.push(format!("You cast {}.", spell_name));
+            }
+
+            let targets = match cast.target {
+                None => Targets::Single { target: entity },
+                Some(target) => match aoe.get(cast.spell) {
+                    None => Targets::Tile {
+                        tile_idx: map.xy_idx(target.x, target.y) as i32,
+                    },
+                    Some(area_effect) => Targets::Area {
+                        center: target,
+                        radius: area_effect.radius,
+                    },
+                },
+            };
+            effects.add_effect(
+                Some(entity),
+                EffectType::SpellUse { spell: cast.spell },
+                targets,
+            );
+        }
+
+        wants_cast.clear();
+    }
+}
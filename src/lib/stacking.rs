@@ -0,0 +1,122 @@
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+use crate::*;
+
+// 成堆的物品
+// 相同的可堆叠物品 在背包中合并为一个带有 Quantity 的实体，丢弃时再拆分出来
+
+// 把一个实体上的组件 复制到另一个实体上
+macro_rules! clone_components {
+    ($ecs:expr, $from:expr, $to:expr, $( $type:ty),*) => {
+        $(
+        let component = $ecs.read_storage::<$type>().get($from).cloned();
+        if let Some(component) = component {
+            $ecs.write_storage::<$type>()
+                .insert($to, component)
+                .expect("Unable to clone component");
+        }
+        )*
+    };
+}
+
+// 从一堆物品中拆出一个，返回拆出的物品，拆出的物品仍然在原来的位置（背包或地图上）
+// 只有一个的时候 直接返回这个物品
+pub fn split_stack(ecs: &mut World, item: Entity) -> Entity {
+    let amount = match ecs.read_storage::<Quantity>().get(item) {
+        Some(quantity) => quantity.amount,
+        None => return item,
+    };
+    if amount < 2 {
+        return item;
+    }
+
+    let single = ecs
+        .create_entity()
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+    clone_components!(
+        ecs,
+        item,
+        single,
+        Position,
+        Renderable,
+        Name,
+        Description,
+        Item,
+        Consumable,
+        Ranged,
+        InflictsDamage,
+        AreaOfEffect,
+        Confusion,
+        ProvidesHealing,
+        InBackpack,
+        ProvidesFood,
+        MagicMapper,
+        ObfuscatedName,
+        ProvidesIdentification,
+        ProvidesRemoveCurse,
+        ProvidesRecharge,
+        Weight,
+        ItemValue,
+        Ammunition,
+        Potion
+    );
+
+    let mut quantities = ecs.write_storage::<Quantity>();
+    quantities
+        .insert(single, Quantity { amount: 1 })
+        .expect("Unable to insert quantity");
+    if let Some(quantity) = quantities.get_mut(item) {
+        quantity.amount -= 1;
+    }
+    single
+}
+
+// 把物品放进 owner 的背包，可以堆叠的物品 合并到已有的那一堆中
+pub fn add_to_backpack(ecs: &mut World, item: Entity, owner: Entity) {
+    ecs.write_storage::<Position>().remove(item);
+    let stack = {
+        let entities = ecs.entities();
+        let names = ecs.read_storage::<Name>();
+        let backpack = ecs.write_storage::<InBackpack>();
+        let quantities = ecs.write_storage::<Quantity>();
+        find_stack(item, owner, &entities, &names, &backpack, &quantities)
+    };
+
+    match stack {
+        Some(stack) => {
+            {
+                let mut quantities = ecs.write_storage::<Quantity>();
+                let amount = quantities.get(item).unwrap().amount;
+                quantities.get_mut(stack).unwrap().amount += amount;
+            }
+            ecs.delete_entity(item)
+                .expect("Unable to delete stacked item");
+        }
+        None => {
+            ecs.write_storage::<InBackpack>()
+                .insert(item, InBackpack { owner })
+                .expect("Unable to insert backpack entry");
+        }
+    }
+}
+
+// 在 owner 的背包中 找到可以和 item 合并的那一堆物品
+pub fn find_stack(
+    item: Entity,
+    owner: Entity,
+    entities: &Entities,
+    names: &ReadStorage<Name>,
+    backpack: &WriteStorage<InBackpack>,
+    quantities: &WriteStorage<Quantity>,
+) -> Option<Entity> {
+    let item_name = &names.get(item)?.name;
+    quantities.get(item)?;
+    (entities, names, backpack, quantities)
+        .join()
+        .find(|(entity, name, pack, _)| {
+            *entity != item && pack.owner == owner && name.name == *item_name
+        })
+        .map(|(entity, _, _, _)| entity)
+}
@@ -0,0 +1,171 @@
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::{
+    attack_bonus, defense_bonus, item_display_name, name_color, ranged_hit, Burden, CombatStats,
+    DefenseBonus, EffectQueue, EffectType, EquipmentSlot, Equippable, Equipped, GameLog,
+    HungerClock, InBackpack, ItemRegistry, Map, MeleePowerBonus, Name, ObfuscatedName, Potion,
+    Renderable, Targets, WantsToThrow,
+};
+
+// 投掷系统
+// 扔出去的武器 造成伤害，药水 摔碎后作用于落点周围，其它的物品 落在落点上
+
+// 投掷的最远距离
+pub const THROW_RANGE: i32 = 6;
+// 药水摔碎后 作用的半径
+pub const SHATTER_RADIUS: i32 = 1;
+
+pub struct ThrowSystem {}
+
+impl<'a> System<'a> for ThrowSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, ItemRegistry>,
+        WriteStorage<'a, WantsToThrow>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, ObfuscatedName>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Equippable>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Burden>,
+        ReadStorage<'a, Potion>,
+        WriteStorage<'a, InBackpack>,
+        WriteExpect<'a, EffectQueue>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Renderable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut log,
+            map,
+            registry,
+            mut wants_throw,
+            names,
+            obfuscated,
+            combat_stats,
+            melee_power_bonuses,
+            defense_bonuses,
+            equipped,
+            equippable,
+            hunger_clock,
+            burdens,
+            potions,
+            mut backpack,
+            mut effects,
+            mut rng,
+            renderables,
+        ) = data;
+
+        for (entity, wants_throw, name) in (&entities, &wants_throw, &names).join() {
+            let item = wants_throw.item;
+            let target = wants_throw.target;
+            let item_name = match names.get(item) {
+                Some(item_name) => item_display_name(item_name, obfuscated.get(item), &registry),
+                None => continue,
+            };
+
+            // 药水摔碎了，和使用药水一样 交给效果系统
+            if potions.get(item).is_some() {
+                backpack.remove(item);
+                log.entry()
+                    .colored(&name.name, name_color(&renderables, entity))
+                    .text(format!(" throws {}, and it shatters!", item_name))
+                    .log();
+                effects.add_effect(
+                    Some(entity),
+                    EffectType::ItemUse { item },
+                    Targets::Area {
+                        center: target,
+                        radius: SHATTER_RADIUS,
+                    },
+                );
+                continue;
+            }
+
+            // 扔出去的武器 砸中落点上的生物
+            let is_weapon = equippable
+                .get(item)
+                .is_some_and(|e| e.slot == EquipmentSlot::Melee);
+            let idx = map.xy_idx(target.x, target.y);
+            let victim = map.tile_content[idx]
+                .iter()
+                .copied()
+                .find(|e| *e != entity && combat_stats.get(*e).is_some_and(|s| s.hp > 0));
+            if let (true, Some(victim), Some(stats)) = (is_weapon, victim, combat_stats.get(entity))
+            {
+                let target_name = names.get(victim).unwrap();
+                let target_stats = combat_stats.get(victim).unwrap();
+                let attacker_color = name_color(&renderables, entity);
+                let target_color = name_color(&renderables, victim);
+                let defensive_bonus = defense_bonus(victim, &defense_bonuses, &equipped, &burdens);
+                if ranged_hit(&mut rng, defensive_bonus) {
+                    let offensive_bonus = attack_bonus(
+                        entity,
+                        true,
+                        &melee_power_bonuses,
+                        &equipped,
+                        &hunger_clock,
+                        &burdens,
+                    );
+                    let weapon_power = melee_power_bonuses.get(item).map_or(0, |b| b.power);
+                    let damage = i32::max(
+                        0,
+                        (stats.power / 2 + weapon_power + offensive_bonus)
+                            - (target_stats.defense + defensive_bonus),
+                    );
+                    if damage == 0 {
+                        log.entry()
+                            .colored(&name.name, attacker_color)
+                            .text(" is unable to hurt ")
+                            .colored(&target_name.name, target_color)
+                            .minor()
+                            .log();
+                    } else {
+                        log.entry()
+                            .colored(&name.name, attacker_color)
+                            .text(format!(" throws {} at ", item_name))
+                            .colored(&target_name.name, target_color)
+                            .text(", for ")
+                            .damage(damage)
+                            .text(" hp.")
+                            .log();
+                        effects.add_effect(
+                            Some(entity),
+                            EffectType::Damage { amount: damage },
+                            Targets::Single { target: victim },
+                        );
+                    }
+                } else {
+                    log.entry()
+                        .colored(&name.name, attacker_color)
+                        .text(format!(" throws {} at ", item_name))
+                        .colored(&target_name.name, target_color)
+                        .text(", but misses.")
+                        .minor()
+                        .log();
+                }
+            }
+
+            // 扔出去的物品 落在落点上
+            effects.add_effect(
+                Some(entity),
+                EffectType::LandItem {
+                    x: target.x,
+                    y: target.y,
+                },
+                Targets::Single { target: item },
+            );
+        }
+
+        wants_throw.clear();
+    }
+}
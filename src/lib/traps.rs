@@ -0,0 +1,137 @@
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+use crate::{
+    Attributes, EffectQueue, EffectType, EntryTrigger, GameLog, Hidden, Mechanism, Name, Position,
+    RunState, Targets, Viewshed,
+};
+
+// 发现 和 解除陷阱
+// 成功的几率都由 感知 决定
+
+// 搜索的范围
+const SEARCH_RADIUS: f32 = 2.5;
+
+// 走路时 看到隐藏陷阱的几率，百分比
+pub fn passive_detection_chance(perception: i32) -> i32 {
+    i32::max(1, perception - 6)
+}
+
+// 专门搜索时 发现陷阱的几率
+pub fn search_chance(perception: i32) -> i32 {
+    i32::min(90, perception * 4)
+}
+
+// 解除陷阱成功的几率
+pub fn disarm_chance(perception: i32) -> i32 {
+    i32::min(95, 40 + (perception - 10) * 5)
+}
+
+fn perception(ecs: &World, entity: Entity) -> i32 {
+    ecs.read_storage::<Attributes>()
+        .get(entity)
+        .map_or(10, |a| a.perception)
+}
+
+// 仔细搜索身边的隐藏物，消耗一回合
+pub fn search_for_traps(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+    let chance = search_chance(perception(ecs, player_entity));
+
+    let mut found: Vec<Entity> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let hidden = ecs.read_storage::<Hidden>();
+        let positions = ecs.read_storage::<Position>();
+        let viewsheds = ecs.read_storage::<Viewshed>();
+        let viewshed = viewsheds.get(player_entity).unwrap();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        for (entity, _hidden, pos) in (&entities, &hidden, &positions).join() {
+            let point = Point::new(pos.x, pos.y);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(player_pos, point);
+            if distance <= SEARCH_RADIUS
+                && viewshed.visible_tiles.contains(&point)
+                && rng.roll_dice(1, 100) <= chance
+            {
+                found.push(entity);
+            }
+        }
+    }
+
+    let mut log_entries: Vec<String> = Vec::new();
+    {
+        let names = ecs.read_storage::<Name>();
+        let mut hidden = ecs.write_storage::<Hidden>();
+        for entity in found.iter() {
+            hidden.remove(*entity);
+            if let Some(name) = names.get(*entity) {
+                log_entries.push(format!("You found a {}.", name.name));
+            }
+        }
+    }
+    if log_entries.is_empty() {
+        log_entries.push("You search the area, but find nothing.".to_string());
+    }
+    ecs.fetch_mut::<GameLog>().extend(log_entries);
+
+    RunState::PlayerTurn
+}
+
+// 解除身边一个已知的陷阱，失败时 有可能触发它
+pub fn disarm_trap(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let player_pos = *ecs.fetch::<Point>();
+
+    // 压力板 也有 EntryTrigger，但它是机关的一部分，不能被解除
+    let trap = {
+        let entities = ecs.entities();
+        let triggers = ecs.read_storage::<EntryTrigger>();
+        let hidden = ecs.read_storage::<Hidden>();
+        let mechanisms = ecs.read_storage::<Mechanism>();
+        let positions = ecs.read_storage::<Position>();
+        (&entities, &triggers, &positions, !&hidden, !&mechanisms)
+            .join()
+            .find(|(_, _, pos, _, _)| {
+                i32::abs(pos.x - player_pos.x) <= 1 && i32::abs(pos.y - player_pos.y) <= 1
+            })
+            .map(|(entity, _, _, _, _)| entity)
+    };
+    let trap = match trap {
+        Some(trap) => trap,
+        None => {
+            ecs.fetch_mut::<GameLog>()
+                .push("There is no trap nearby to disarm.");
+            return RunState::AwaitingInput;
+        }
+    };
+
+    let name = ecs.read_storage::<Name>().get(trap).unwrap().name.clone();
+    let chance = disarm_chance(perception(ecs, player_entity));
+    let (disarmed, triggered) = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let disarmed = rng.roll_dice(1, 100) <= chance;
+        (disarmed, !disarmed && rng.roll_dice(1, 2) == 1)
+    };
+
+    if disarmed {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("You disarm the {}.", name));
+        ecs.entities().delete(trap).expect("Unable to delete trap");
+    } else if triggered {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("You fail to disarm the {}, and set it off!", name));
+        ecs.write_resource::<EffectQueue>().add_effect(
+            Some(player_entity),
+            EffectType::TriggerFire { trigger: trap },
+            Targets::Single {
+                target: player_entity,
+            },
+        );
+    } else {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("You fail to disarm the {}.", name));
+    }
+
+    RunState::PlayerTurn
+}
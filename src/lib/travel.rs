@@ -0,0 +1,584 @@
+use std::collections::HashSet;
+
+use rltk::{Algorithm2D, BaseMap, DijkstraMap, Point};
+use specs::prelude::*;
+
+use crate::{
+    item_display_name, tile_blocks, try_move_player, CombatStats, EntryTrigger, GameLog, Hidden,
+    HungerClock, HungerState, Item, ItemRegistry, Map, Monster, Name, ObfuscatedName, Position,
+    RunState, TileType, Viewshed,
+};
+
+// 自动寻路、自动探索 和 跑步
+// 用鼠标点击 已经探索过的位置，玩家一步一步走过去，每一步一个回合，遇到意外时停下来
+// 自动探索时 每一步都走向最近的 还没有探索过的地块
+// 跑步时 沿着一个方向 一直走，在走廊里 会跟着走廊拐弯
+
+// 当前的行程，作为资源插入 ecs 中，不随游戏保存
+#[derive(Default)]
+pub struct Travel {
+    // 剩下的路径，倒序存放，下一步在最后
+    path: Vec<Point>,
+    // 自动探索，每一步重新计算方向
+    exploring: bool,
+    // 跑步的方向，每一步后 更新为这一步的方向
+    running: Option<(i32, i32)>,
+    // 是否在走廊里跑
+    corridor: bool,
+    // 上一步所在的位置 周围可以走的地块数，在房间里跑时 周围的地形变了就停下
+    open_count: i32,
+    // 出发时所在的层
+    depth: i32,
+    // 上一步时的生命值，生命值减少说明受到了攻击
+    hp: i32,
+    // 上一步时的饥饿程度
+    hunger: i32,
+    // 出发后 已经看见过的怪物，跑步时 还有物品
+    seen: HashSet<Entity>,
+    // 已经走了几步
+    steps: i32,
+}
+
+impl Travel {
+    pub fn is_active(&self) -> bool {
+        self.exploring || self.running.is_some() || !self.path.is_empty()
+    }
+
+    pub fn stop(&mut self) {
+        self.path.clear();
+        self.exploring = false;
+        self.running = None;
+    }
+}
+
+// 只在探索过的地块上寻路，绕开阻挡的地块 和 已知的陷阱
+struct TravelMap<'a> {
+    map: &'a Map,
+    avoid: HashSet<usize>,
+}
+
+impl TravelMap<'_> {
+    fn is_passable(&self, x: i32, y: i32) -> bool {
+        if x < 1 || x > self.map.width - 1 || y < 1 || y > self.map.height - 1 {
+            return false;
+        }
+        let idx = self.map.xy_idx(x, y);
+        self.map.revealed_tiles[idx] && !self.map.blocked[idx] && !self.avoid.contains(&idx)
+    }
+}
+
+impl BaseMap for TravelMap<'_> {
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        let p1 = self.index_to_point2d(idx1);
+        let p2 = self.index_to_point2d(idx2);
+        rltk::DistanceAlg::Pythagoras.distance2d(p1, p2)
+    }
+
+    fn get_available_exits(&self, idx: usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        let mut exits = rltk::SmallVec::new();
+        let p = self.index_to_point2d(idx);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) == (0, 0) || !self.is_passable(p.x + dx, p.y + dy) {
+                    continue;
+                }
+                let cost = if dx != 0 && dy != 0 { 1.45 } else { 1.0 };
+                exits.push((self.point2d_to_index(Point::new(p.x + dx, p.y + dy)), cost));
+            }
+        }
+        exits
+    }
+}
+
+impl Algorithm2D for TravelMap<'_> {
+    fn dimensions(&self) -> Point {
+        Point::new(self.map.width, self.map.height)
+    }
+}
+
+// 玩家到目标位置的路径，不包括玩家当前的位置
+fn find_path(ecs: &World, target: Point) -> Option<Vec<Point>> {
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let avoid = (
+        &ecs.read_storage::<EntryTrigger>(),
+        &ecs.read_storage::<Position>(),
+        !&ecs.read_storage::<Hidden>(),
+    )
+        .join()
+        .map(|(_, pos, _)| map.xy_idx(pos.x, pos.y))
+        .collect();
+    let travel_map = TravelMap { map: &map, avoid };
+
+    let path = rltk::a_star_search(
+        travel_map.point2d_to_index(player_pos),
+        travel_map.point2d_to_index(target),
+        &travel_map,
+    );
+    if path.success && path.steps.len() > 1 {
+        Some(
+            path.steps[1..]
+                .iter()
+                .map(|idx| travel_map.index_to_point2d(*idx))
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
+// 最近的 还没有探索过的地块 的方向，不能继续探索时 返回原因
+fn explore_step(ecs: &World) -> Result<Point, &'static str> {
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let avoid = (
+        &ecs.read_storage::<EntryTrigger>(),
+        &ecs.read_storage::<Position>(),
+        !&ecs.read_storage::<Hidden>(),
+    )
+        .join()
+        .map(|(_, pos, _)| map.xy_idx(pos.x, pos.y))
+        .collect();
+    let travel_map = TravelMap { map: &map, avoid };
+
+    // 和探索过的地块相邻的 没有探索过的地块
+    let mut starts = Vec::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if map.revealed_tiles[map.xy_idx(x, y)] {
+                continue;
+            }
+            let frontier = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .any(|(dx, dy)| travel_map.is_passable(x + dx, y + dy));
+            if frontier {
+                starts.push(travel_map.point2d_to_index(Point::new(x, y)));
+            }
+        }
+    }
+    if starts.is_empty() {
+        return Err("There is nothing left to explore.");
+    }
+
+    let dijkstra = DijkstraMap::new(map.width, map.height, &starts, &travel_map, 2000.0);
+    let player_idx = travel_map.point2d_to_index(player_pos);
+    // 还有没探索过的地块，但是都走不过去时 和探索完了 分开提示
+    DijkstraMap::find_lowest_exit(&dijkstra, player_idx, &travel_map)
+        .filter(|idx| dijkstra.map[*idx] < f32::MAX)
+        .map(|idx| travel_map.index_to_point2d(idx))
+        .ok_or("You can't find a way to the unexplored areas.")
+}
+
+// 地块上 能不能走，不考虑上面的实体
+fn is_open(map: &Map, x: i32, y: i32) -> bool {
+    x >= 0 && x < map.width && y >= 0 && y < map.height && !tile_blocks(map.tiles[map.xy_idx(x, y)])
+}
+
+// 周围八个地块中 可以走的数量
+fn open_neighbours(map: &Map, pos: Point) -> i32 {
+    let mut count = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if (dx, dy) != (0, 0) && is_open(map, pos.x + dx, pos.y + dy) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// 跑步方向 左右两侧的地块 都走不过去，说明在走廊里
+// 房间的角落 周围能走的地块也很少，但是总有一侧 是空的
+fn walled_in(map: &Map, pos: Point, dx: i32, dy: i32) -> bool {
+    !is_open(map, pos.x - dy, pos.y + dx) && !is_open(map, pos.x + dy, pos.y - dx)
+}
+
+// 跑步的下一步，到了岔路口、门口、楼梯、房间的入口 或者 周围的地形变了 返回 None
+fn run_step(ecs: &World) -> Option<Point> {
+    let travel = ecs.fetch::<Travel>();
+    let map = ecs.fetch::<Map>();
+    let pos = *ecs.fetch::<Point>();
+    let (dx, dy) = travel.running?;
+    let ahead = Point::new(pos.x + dx, pos.y + dy);
+
+    // 第一步 只看前面能不能走
+    if travel.steps == 0 {
+        return Some(ahead).filter(|p| is_open(&map, p.x, p.y));
+    }
+    if matches!(
+        map.tiles[map.xy_idx(pos.x, pos.y)],
+        TileType::OpenDoor | TileType::DownStairs
+    ) {
+        return None;
+    }
+
+    if travel.corridor {
+        // 不往回走，和跑步方向相反的地块 都不算
+        let mut orthogonal = Vec::new();
+        let mut diagonal = Vec::new();
+        for ny in -1..=1 {
+            for nx in -1..=1 {
+                let p = Point::new(pos.x + nx, pos.y + ny);
+                let behind = nx * dx + ny * dy < 0;
+                if (nx, ny) == (0, 0) || behind || !is_open(&map, p.x, p.y) {
+                    continue;
+                }
+                if nx == 0 || ny == 0 {
+                    orthogonal.push(p);
+                } else {
+                    diagonal.push(p);
+                }
+            }
+        }
+        // 只有一条路时 跟着走廊拐弯，有多条路 就是到了岔路口 或者 房间的入口
+        match orthogonal.len() {
+            0 if diagonal.len() == 1 => Some(diagonal[0]),
+            1 => {
+                let next = orthogonal[0];
+                let branch = diagonal
+                    .iter()
+                    .any(|p| (p.x - next.x).abs() > 1 || (p.y - next.y).abs() > 1);
+                if branch {
+                    None
+                } else {
+                    Some(next)
+                }
+            }
+            _ => None,
+        }
+    } else {
+        // 在房间里 一直往前跑，经过墙上的开口 或者 走到墙边 时停下
+        if !is_open(&map, ahead.x, ahead.y) {
+            return None;
+        }
+        if travel.steps > 1 && open_neighbours(&map, pos) != travel.open_count {
+            return None;
+        }
+        Some(ahead)
+    }
+}
+
+// 玩家视野中的物品
+fn visible_items(ecs: &World) -> Vec<Entity> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let viewshed = match viewsheds.get(player_entity) {
+        Some(viewshed) => viewshed,
+        None => return Vec::new(),
+    };
+    (
+        &ecs.entities(),
+        &ecs.read_storage::<Item>(),
+        &ecs.read_storage::<Position>(),
+    )
+        .join()
+        .filter(|(_, _, pos)| viewshed.visible_tiles.contains(&Point::new(pos.x, pos.y)))
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+// 玩家视野中的怪物
+fn visible_monsters(ecs: &World) -> Vec<Entity> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let viewshed = match viewsheds.get(player_entity) {
+        Some(viewshed) => viewshed,
+        None => return Vec::new(),
+    };
+    (
+        &ecs.entities(),
+        &ecs.read_storage::<Monster>(),
+        &ecs.read_storage::<Position>(),
+    )
+        .join()
+        .filter(|(_, _, pos)| viewshed.visible_tiles.contains(&Point::new(pos.x, pos.y)))
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+fn player_hp(ecs: &World) -> i32 {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.read_storage::<CombatStats>()
+        .get(player_entity)
+        .map_or(0, |stats| stats.hp)
+}
+
+// 饥饿的程度，越饿越大
+fn hunger_level(ecs: &World) -> i32 {
+    let player_entity = *ecs.fetch::<Entity>();
+    match ecs.read_storage::<HungerClock>().get(player_entity) {
+        Some(clock) => match clock.state {
+            HungerState::WellFed => 0,
+            HungerState::Normal => 1,
+            HungerState::Hungry => 2,
+            HungerState::Starving => 3,
+        },
+        None => 0,
+    }
+}
+
+// 出发时的状态
+fn departure(ecs: &World) -> Travel {
+    Travel {
+        depth: ecs.fetch::<Map>().depth,
+        hp: player_hp(ecs),
+        hunger: hunger_level(ecs),
+        seen: visible_monsters(ecs).into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+fn begin_travel(ecs: &mut World, travel: Travel) -> RunState {
+    ecs.insert(travel);
+    travel_step(ecs)
+}
+
+// 朝一个方向跑，旁边有怪物时 和普通的移动一样 攻击它
+pub fn start_run(ecs: &mut World, dx: i32, dy: i32) -> RunState {
+    let pos = *ecs.fetch::<Point>();
+    let (corridor, open_count, occupied) = {
+        let map = ecs.fetch::<Map>();
+        let ahead = Point::new(pos.x + dx, pos.y + dy);
+        let occupied = is_open(&map, ahead.x, ahead.y) && map.blocked[map.xy_idx(ahead.x, ahead.y)];
+        // 站在走廊的拐角上时 两侧不全是墙，再看看前面的一格
+        (
+            walled_in(&map, pos, dx, dy) || walled_in(&map, ahead, dx, dy),
+            open_neighbours(&map, pos),
+            occupied,
+        )
+    };
+    if occupied {
+        return try_move_player(dx, dy, ecs);
+    }
+    let mut travel = Travel {
+        running: Some((dx, dy)),
+        corridor,
+        open_count,
+        ..departure(ecs)
+    };
+    travel.seen.extend(visible_items(ecs));
+    begin_travel(ecs, travel)
+}
+
+// 自动探索，视野中有怪物时 不能开始
+pub fn auto_explore(ecs: &mut World) -> RunState {
+    let monster = visible_monsters(ecs).into_iter().find_map(|monster| {
+        ecs.read_storage::<Name>()
+            .get(monster)
+            .map(|name| name.name.clone())
+    });
+    if let Some(name) = monster {
+        ecs.fetch_mut::<GameLog>()
+            .push(format!("You can't explore with {} in view.", name));
+        return RunState::AwaitingInput;
+    }
+    let travel = Travel {
+        exploring: true,
+        ..departure(ecs)
+    };
+    begin_travel(ecs, travel)
+}
+
+// 走向地图上的某个位置，点在地图外面时 什么也不做
+pub fn travel_to(ecs: &mut World, target: Point) -> RunState {
+    let (width, height) = {
+        let map = ecs.fetch::<Map>();
+        (map.width, map.height)
+    };
+    let player_pos = *ecs.fetch::<Point>();
+    if target.x < 0 || target.x >= width || target.y < 0 || target.y >= height {
+        return RunState::AwaitingInput;
+    }
+    if target == player_pos {
+        return RunState::AwaitingInput;
+    }
+    match find_path(ecs, target) {
+        Some(mut path) => {
+            path.reverse();
+            let travel = Travel {
+                path,
+                ..departure(ecs)
+            };
+            begin_travel(ecs, travel)
+        }
+        None => {
+            ecs.fetch_mut::<GameLog>()
+                .push("You don't know a way there.");
+            RunState::AwaitingInput
+        }
+    }
+}
+
+// 走向已经发现的 下楼的楼梯
+pub fn travel_to_stairs(ecs: &mut World) -> RunState {
+    let stairs = {
+        let map = ecs.fetch::<Map>();
+        let mut stairs = None;
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] == TileType::DownStairs && map.revealed_tiles[idx] {
+                    stairs = Some(Point::new(x, y));
+                }
+            }
+        }
+        stairs
+    };
+    let player_pos = *ecs.fetch::<Point>();
+    match stairs {
+        None => {
+            ecs.fetch_mut::<GameLog>()
+                .push("You haven't found the way down yet.");
+            RunState::AwaitingInput
+        }
+        Some(stairs) if stairs == player_pos => {
+            ecs.fetch_mut::<GameLog>()
+                .push("You are already standing on the stairs.");
+            RunState::AwaitingInput
+        }
+        Some(stairs) => travel_to(ecs, stairs),
+    }
+}
+
+// 需要停下来的原因
+fn interruption(ecs: &World, next: Point) -> Option<String> {
+    let travel = ecs.fetch::<Travel>();
+    let map = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let names = ecs.read_storage::<Name>();
+
+    if player_hp(ecs) < travel.hp {
+        return Some("You are hurt, and stop.".to_string());
+    }
+
+    let hunger = hunger_level(ecs);
+    if hunger > travel.hunger {
+        let reason = match hunger {
+            1 => "You are no longer well fed, and stop.",
+            2 => "You are getting hungry, and stop.",
+            _ => "You are starving, and stop.",
+        };
+        return Some(reason.to_string());
+    }
+
+    if let Some(monster) = visible_monsters(ecs)
+        .into_iter()
+        .find(|monster| !travel.seen.contains(monster))
+    {
+        if let Some(name) = names.get(monster) {
+            return Some(format!("{} comes into view.", name.name));
+        }
+    }
+
+    // 跑步时 看到新的物品 也停下来
+    if travel.running.is_some() {
+        let registry = ecs.fetch::<ItemRegistry>();
+        let obfuscated = ecs.read_storage::<ObfuscatedName>();
+        if let Some(item) = visible_items(ecs)
+            .into_iter()
+            .find(|item| !travel.seen.contains(item))
+        {
+            if let Some(name) = names.get(item) {
+                return Some(format!(
+                    "You see {}.",
+                    item_display_name(name, obfuscated.get(item), &registry)
+                ));
+            }
+        }
+    }
+
+    // 踩到了物品上，自动探索时 不理会地上的物品
+    if travel.steps > 0 && !travel.exploring {
+        let registry = ecs.fetch::<ItemRegistry>();
+        let obfuscated = ecs.read_storage::<ObfuscatedName>();
+        if let Some((item, _, _)) = (
+            &ecs.entities(),
+            &ecs.read_storage::<Item>(),
+            &ecs.read_storage::<Position>(),
+        )
+            .join()
+            .find(|(_, _, pos)| pos.x == player_pos.x && pos.y == player_pos.y)
+        {
+            if let Some(name) = names.get(item) {
+                return Some(format!(
+                    "You find {} here.",
+                    item_display_name(name, obfuscated.get(item), &registry)
+                ));
+            }
+        }
+    }
+
+    if map.blocked[map.xy_idx(next.x, next.y)] {
+        return Some("Something blocks your way.".to_string());
+    }
+    None
+}
+
+// 沿着路径 或者 朝着没有探索过的地方 走一步，走完 或者 被打断时 回到等待输入
+pub fn travel_step(ecs: &mut World) -> RunState {
+    let player_pos = *ecs.fetch::<Point>();
+    let (exploring, running, planned, depth) = {
+        let travel = ecs.fetch::<Travel>();
+        (
+            travel.exploring,
+            travel.running.is_some(),
+            travel.path.last().copied(),
+            travel.depth,
+        )
+    };
+    let next = if exploring {
+        match explore_step(ecs) {
+            Ok(next) => Some(next),
+            Err(reason) => {
+                ecs.fetch_mut::<GameLog>().push(reason);
+                None
+            }
+        }
+    } else if running {
+        run_step(ecs)
+    } else {
+        planned
+    };
+    let next = match next {
+        Some(next) => next,
+        None => {
+            ecs.fetch_mut::<Travel>().stop();
+            return RunState::AwaitingInput;
+        }
+    };
+    // 换了一层 或者 被传送走了，路径已经没用了
+    if ecs.fetch::<Map>().depth != depth
+        || (next.x - player_pos.x).abs() > 1
+        || (next.y - player_pos.y).abs() > 1
+    {
+        ecs.fetch_mut::<Travel>().stop();
+        return RunState::AwaitingInput;
+    }
+
+    if let Some(reason) = interruption(ecs, next) {
+        ecs.fetch_mut::<Travel>().stop();
+        ecs.fetch_mut::<GameLog>().push(reason);
+        return RunState::AwaitingInput;
+    }
+
+    let hp = player_hp(ecs);
+    let hunger = hunger_level(ecs);
+    let open_count = open_neighbours(&ecs.fetch::<Map>(), player_pos);
+    {
+        let mut travel = ecs.fetch_mut::<Travel>();
+        travel.path.pop();
+        travel.hp = hp;
+        travel.hunger = hunger;
+        travel.steps += 1;
+        if running {
+            travel.running = Some((next.x - player_pos.x, next.y - player_pos.y));
+            travel.open_count = open_count;
+        }
+    }
+    // 撞上了商人、拉杆 或者 怪物，没有走到下一步的位置，不再继续走
+    let result = try_move_player(next.x - player_pos.x, next.y - player_pos.y, ecs);
+    if result != RunState::PlayerTurn || *ecs.fetch::<Point>() != next {
+        ecs.fetch_mut::<Travel>().stop();
+    }
+    result
+}
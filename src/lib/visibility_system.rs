@@ -1,4 +1,4 @@
-use crate::{GameLog, Hidden, Map, Name, Player};
+use crate::{passive_detection_chance, Attributes, GameLog, Hidden, Map, Name, Player};
 
 use super::{Position, Viewshed};
 use rltk::{field_of_view, Point};
@@ -12,6 +12,7 @@ pub struct VisibilitySystem {}
 // 向RLTK请求视域所需要的内容，Ｍap  ReadExpect<'a, Map>,
 impl<'a> System<'a> for VisibilitySystem {
     // 系统需要使用的数据
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
         Entities<'a>,
@@ -22,11 +23,22 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteExpect<'a, rltk::RandomNumberGenerator>,
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, entities, mut viewshed, pos, player, mut hidden, mut rng, mut log, names) =
-            data;
+        let (
+            mut map,
+            entities,
+            mut viewshed,
+            pos,
+            player,
+            mut hidden,
+            mut rng,
+            mut log,
+            names,
+            attributes,
+        ) = data;
 
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
@@ -45,6 +57,9 @@ impl<'a> System<'a> for VisibilitySystem {
                 // if this is the player , reveal what they can see
                 let _p: Option<&Player> = player.get(ent);
                 if let Some(_p) = _p {
+                    // 感知越高 越容易发现隐藏的陷阱
+                    let detection_chance =
+                        passive_detection_chance(attributes.get(ent).map_or(10, |a| a.perception));
                     for t in map.visible_tiles.iter_mut() {
                         *t = false
                     }
@@ -57,11 +72,10 @@ impl<'a> System<'a> for VisibilitySystem {
                         for e in map.tile_content[idx].iter() {
                             let maybe_hidden = hidden.get(*e);
                             if let Some(_maybe_hidden) = maybe_hidden {
-                                // 发现陷阱的机会只有1/24
-                                if rng.roll_dice(1, 24) == 1 {
+                                if rng.roll_dice(1, 100) <= detection_chance {
                                     let name = names.get(*e);
                                     if let Some(name) = name {
-                                        log.entries.push(format!("You spotted a {}.", &name.name));
+                                        log.push(format!("You spotted a {}.", &name.name));
                                     }
                                     // 将实体 *e 从hidden 组件存储器中移除, *e 可以被看见
                                     hidden.remove(*e);
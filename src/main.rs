@@ -5,18 +5,32 @@ use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 use tutorial::*; // use tutorial lib
-const SHOW_MAPGEN_VISUALIZER : bool = true;
 fn main() -> rltk::BError {
     // use builder
     use rltk::RltkBuilder;
 
-    // initialise context
-    let mut context = RltkBuilder::simple80x50()
+    // 读取设置文件，设置文件有错时 提示错误并使用默认的设置
+    let settings = match settings::load_settings() {
+        Ok(settings) => settings,
+        Err(error) => {
+            eprintln!("{}", error);
+            settings::Settings::default()
+        }
+    };
+
+    // initialise context，字体 和 窗口的大小 由设置决定
+    let builder = match settings.font {
+        settings::Font::Terminal8x8 => RltkBuilder::simple80x50(),
+        settings::Font::Vga8x16 => RltkBuilder::vga80x50(),
+    };
+    let (tile_width, tile_height) = settings.font.tile_size();
+    let mut context = builder
+        .with_tile_dimensions(tile_width * settings.scale, tile_height * settings.scale)
         .with_title("Roguelike Tutorial")
         .build()?;
 
     // 地图显示复古的感觉
-    context.with_post_scanlines(true);
+    context.with_post_scanlines(settings.scanlines);
 
     // 游戏状态，包含游戏世界和游戏的运行状态
     let mut gs = State {
@@ -36,11 +50,11 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Player>();
     gs.ecs.register::<Monster>();
     gs.ecs.register::<Name>();
+    gs.ecs.register::<Description>();
 
     gs.ecs.register::<Viewshed>(); // 将组件注册到系统中
     gs.ecs.register::<BlocksTile>();
     gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
     gs.ecs.register::<CombatStats>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<Confusion>();
@@ -78,6 +92,9 @@ fn main() -> rltk::BError {
     // 将ParticleBuilder 作为资源
     gs.ecs.insert(particle_system::ParticleBuilder::new());
 
+    // 效果队列 作为资源
+    gs.ecs.insert(effects::EffectQueue::new());
+
     // hunger food
     gs.ecs.register::<HungerClock>();
     gs.ecs.register::<ProvidesFood>();
@@ -91,9 +108,74 @@ fn main() -> rltk::BError {
     gs.ecs.register::<EntityMoved>();
     gs.ecs.register::<SingleActivation>();
 
+    // 物品鉴定
+    gs.ecs.register::<ObfuscatedName>();
+    gs.ecs.register::<ProvidesIdentification>();
+    gs.ecs.register::<Cursed>();
+    gs.ecs.register::<ProvidesRemoveCurse>();
+
+    // 充能
+    gs.ecs.register::<Charges>();
+    gs.ecs.register::<ProvidesRecharge>();
+
+    // 物品堆叠
+    gs.ecs.register::<Quantity>();
+
+    // 负重
+    gs.ecs.register::<Weight>();
+    gs.ecs.register::<Attributes>();
+    gs.ecs.register::<Burden>();
+    gs.ecs.register::<KillCount>();
+
+    // 双手武器
+    gs.ecs.register::<TwoHanded>();
+
+    // 陷阱
+    gs.ecs.register::<RandomTeleport>();
+    gs.ecs.register::<Alarm>();
+    gs.ecs.register::<InflictsPoison>();
+    gs.ecs.register::<Poisoned>();
+    gs.ecs.register::<TrapDoor>();
+    gs.ecs.register::<SummonsMonsters>();
+    gs.ecs.register::<Asleep>();
+
+    // 远程武器
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<Ammunition>();
+    gs.ecs.register::<WantsToShoot>();
+
+    // 投掷
+    gs.ecs.register::<Potion>();
+    gs.ecs.register::<WantsToThrow>();
+
+    // 机关
+    gs.ecs.register::<Mechanism>();
+    gs.ecs.register::<Lever>();
+
+    // 法术
+    gs.ecs.register::<Mana>();
+    gs.ecs.register::<Spell>();
+    gs.ecs.register::<KnownSpell>();
+    gs.ecs.register::<TeachesSpell>();
+    gs.ecs.register::<WantsToCastSpell>();
+
+    // 附魔的装备
+    gs.ecs.register::<ElementalDamage>();
+    gs.ecs.register::<AttributeBonus>();
+    gs.ecs.register::<LifeSteal>();
+    gs.ecs.register::<ConfuseOnHit>();
+    gs.ecs.register::<Affixed>();
+
+    // 金币 和 商店
+    gs.ecs.register::<Wallet>();
+    gs.ecs.register::<Gold>();
+    gs.ecs.register::<ItemValue>();
+    gs.ecs.register::<Vendor>();
+
     // ------------------create entity 创建实体 ----------------------------------------------------
     // level 1 创建地图
-    let mut builder = map_builders::random_builder(1);
+    // 启动时生成的地图 不显示生成的过程
+    let mut builder = map_builders::random_builder(1, false);
     builder.build_map();
     let player_start = builder.get_starting_position();
     let map = builder.get_map();
@@ -107,12 +189,15 @@ fn main() -> rltk::BError {
     // 随机数 生成器 作为一种 资源 随机创建
     gs.ecs.insert(rltk::RandomNumberGenerator::new());
 
-    builder.spawn_entities(&mut gs.ecs);
+    // 已知物品的登记表，每局游戏随机生成物品的外观名字
+    gs.ecs.insert(identification::ItemRegistry::new());
 
     // --------------add resource in world  ,shared data the whole ecs can use --------------------------------
-    // 将map 插到world 中
+    // 将map 插到world 中，生成实体时 需要用到地图
     gs.ecs.insert(map);
 
+    builder.spawn_entities(&mut gs.ecs);
+
     // 将玩家的位置作为 资源 插入 ecs 中 Point 是表示玩家位置的资源
     gs.ecs.insert(Point::new(player_x, player_y));
 
@@ -123,9 +208,27 @@ fn main() -> rltk::BError {
     gs.ecs.insert(RunState::MapGeneration{} );
 
     // 插入日志 作为资源
-    gs.ecs.insert(gamelog::GameLog {
-        entries: vec!["Welcome to Rusty Roguelike".to_string()],
-    });
+    let mut gamelog = gamelog::GameLog::new();
+    gamelog.push("Welcome to Rusty Roguelike");
+
+    // 按键绑定，配置文件有错时 提示错误并使用默认的按键
+    let keymap = match keymap::load_keymap() {
+        Ok(keymap) => keymap,
+        Err(error) => {
+            eprintln!("{}", error);
+            gamelog.entry().colored(error, RGB::named(rltk::RED)).log();
+            gamelog.push("Using the default key bindings.");
+            keymap::KeyMap::default_keys()
+        }
+    };
+    gs.ecs.insert(keymap);
+
+    // 自动寻路的行程
+    gs.ecs.insert(travel::Travel::default());
+
+    // 游戏的设置
+    gs.ecs.insert(settings);
+    gs.ecs.insert(gamelog);
 
     //  insert the dungeon graphic into Specs as a resource so we can access our sprites anywhere
     gs.ecs.insert(rex_assets::RexAssets::new());